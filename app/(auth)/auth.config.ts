@@ -24,6 +24,10 @@ export const authConfig = {
         return true; // Always allow access to register and login pages
       }
 
+      if (nextUrl.pathname === "/api/files/upload-token") {
+        return true; // Checks its own session; Blob's completion callback has none
+      }
+
       if (isOnChat) {
         if (isLoggedIn) return true;
         return false; // Redirect unauthenticated users to login page
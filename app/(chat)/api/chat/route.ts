@@ -17,7 +17,31 @@ import {
   saveChat,
 } from "@/db/queries";
 import { generateUUID } from "@/lib/utils";
-import { videoProcessor } from "@/lib/video-processor";
+import { FPS_PRESETS, ProcessingError, videoProcessor } from "@/lib/video-processor";
+
+const overlayPlacementParameters = {
+  position: z.enum(["top-left", "top-right", "bottom-left", "bottom-right", "center"]).optional().describe("Where to place it (default bottom-right)"),
+  x: z.number().optional().describe("Exact left offset in pixels; overrides position"),
+  y: z.number().optional().describe("Exact top offset in pixels; overrides position"),
+  scale: z.number().positive().max(1).optional().describe("Width as a fraction of the frame width (default 0.15)"),
+  opacity: z.number().min(0).max(1).optional().describe("Opacity from 0 to 1 (default 1)"),
+  margin: z.number().min(0).optional().describe("Distance from the frame edges in pixels (default 20)"),
+};
+
+// Processing errors say what went wrong in terms the user can act on (an
+// edit this server's FFmpeg can't run, a size limit, a full queue); anything
+// else gets the tool's generic message
+function toolError(error: unknown, fallback: string): string {
+  return error instanceof ProcessingError ? error.message : fallback;
+}
+
+const outputNameParameter = z
+  .string()
+  .optional()
+  .describe(
+    "Optional human-readable file name for the edited video, e.g. 'product-demo-intro'. " +
+    "May use {input_stem}, {operation}, {timestamp}, {date} and {ext}, e.g. '{input_stem}_{operation}_{date}'",
+  );
 
 export async function POST(request: Request) {
   const { id, messages }: { id: string; messages: Array<Message> } =
@@ -61,6 +85,18 @@ export async function POST(request: Request) {
         2. Use the returned videoUrl in subsequent video editing tool calls
         3. Then apply the requested edits using the appropriate tools
         
+        For uploaded images, call getImageFromAttachments instead and pass the imageUrl to the
+        editing tools; brightness, text, crop, rotate, filter, resize and format conversion work on images too.
+        For uploaded audio, call getAudioFromAttachments and pass the audioUrl; trim, volume, normalize,
+        fade, transcode and waveform work on audio files.
+        To put new sound under a video, get both URLs and call replaceAudio.
+        To make a video from uploaded images, call createSlideshow with the image URLs in order.
+        To brand a video with an uploaded logo, get both URLs and call addWatermark.
+        To replace a green screen, get the video and the background URLs and call chromaKey.
+        Old broadcast, DV or camcorder footage is often interlaced; offer deinterlace before other edits so it doesn't come out combed.
+        Edits that take several videos (composeGrid, concatVideos) can use videos uploaded in earlier messages
+        or the results of earlier edits.
+        
         DO NOT use placeholder URLs like "YOUR_VIDEO_URL" - always get the real URL first!
         
         Example workflow for "trim the first 20 seconds":
@@ -124,48 +160,209 @@ export async function POST(request: Request) {
             console.error("Failed to get video from attachments:", error);
             return {
               success: false,
-              error: "Failed to extract video URL"
+              error: toolError(error, "Failed to extract video URL")
+            };
+          }
+        },
+      },
+      getImageFromAttachments: {
+        description: "Extract the image URL from the user's message attachments. Use this first when the user has uploaded an image and wants to edit it.",
+        parameters: z.object({}),
+        execute: async ({}) => {
+          // Look for the most recent user message with image attachments
+          const userMessages = messages.filter(m => m.role === 'user');
+          
+          for (let i = userMessages.length - 1; i >= 0; i--) {
+            const imageAttachment = userMessages[i].experimental_attachments?.find(
+              att => att.contentType?.startsWith('image/')
+            );
+            
+            if (imageAttachment) {
+              return {
+                success: true,
+                imageUrl: imageAttachment.url,
+                imageName: imageAttachment.name || 'Uploaded image',
+                message: `Found image: ${imageAttachment.name || 'Uploaded image'}`
+              };
+            }
+          }
+          
+          return {
+            success: false,
+            error: "No image found in recent messages. Please upload an image first using the image upload button."
+          };
+        },
+      },
+      getAudioFromAttachments: {
+        description: "Extract the audio file URL from the user's message attachments. Use this first when the user has uploaded an audio file and wants to edit it.",
+        parameters: z.object({}),
+        execute: async ({}) => {
+          // Look for the most recent user message with audio attachments
+          const userMessages = messages.filter(m => m.role === 'user');
+          
+          for (let i = userMessages.length - 1; i >= 0; i--) {
+            const audioAttachment = userMessages[i].experimental_attachments?.find(
+              att => att.contentType?.startsWith('audio/')
+            );
+            
+            if (audioAttachment) {
+              return {
+                success: true,
+                audioUrl: audioAttachment.url,
+                audioName: audioAttachment.name || 'Uploaded audio',
+                message: `Found audio: ${audioAttachment.name || 'Uploaded audio'}`
+              };
+            }
+          }
+          
+          return {
+            success: false,
+            error: "No audio found in recent messages. Please upload an audio file first using the audio upload button."
+          };
+        },
+      },
+      getAvailableOperations: {
+        description: "List which video editing tools the server's FFmpeg build supports. Use this when an edit fails as unavailable or the user asks what can be done.",
+        parameters: z.object({}),
+        execute: async ({}) => {
+          try {
+            const operations = await videoProcessor.getOperationAvailability();
+            const unavailable = Object.keys(operations).filter(name => !operations[name].available);
+            return {
+              success: true,
+              operations,
+              message: unavailable.length > 0
+                ? `Unavailable on this server: ${unavailable.join(', ')}`
+                : "All video editing tools are available"
+            };
+          } catch (error) {
+            console.error("Failed to detect FFmpeg capabilities:", error);
+            return {
+              success: false,
+              error: toolError(error, "Failed to detect FFmpeg capabilities"),
             };
           }
         },
       },
       trimVideo: {
-        description: "Trim a video to specified start and end times",
+        description: "Trim a video to specified start and end times, or pull out several ranges at once as separate clips or one joined highlight video",
         parameters: z.object({
           videoUrl: z.string().describe("URL of the video to trim"),
-          startTime: z.number().describe("Start time in seconds"),
-          endTime: z.number().describe("End time in seconds"),
+          startTime: z.union([z.number(), z.string()]).optional().describe("Start as seconds, an HH:MM:SS.mmm timecode, or a frame number like '250f'"),
+          endTime: z.union([z.number(), z.string()]).optional().describe("End as seconds, an HH:MM:SS.mmm timecode, or a frame number like '250f'"),
+          ranges: z.array(z.object({
+            start: z.union([z.number(), z.string()]).describe("Range start, in the same formats as startTime"),
+            end: z.union([z.number(), z.string()]).describe("Range end, in the same formats as endTime"),
+          })).min(1).optional().describe("Several ranges to extract instead of a single startTime/endTime"),
+          join: z.boolean().optional().describe("With ranges, join them in order into one video instead of returning a clip per range"),
+          bundle: z.boolean().optional().describe("With ranges and no join, return the clips as a single zip download"),
+          accurate: z.boolean().optional().describe("Cut on the exact frame by re-encoding instead of snapping to keyframes (slower)"),
+          outputName: outputNameParameter,
         }),
-        execute: async ({ videoUrl, startTime, endTime }) => {
+        execute: async ({ videoUrl, startTime, endTime, ranges, join = false, bundle = false, accurate = false, outputName }) => {
+          if (ranges) {
+            try {
+              const { url: editedUrl, urls, originalFilename, timings, attempts } = await videoProcessor.extractClips(videoUrl, ranges, join, accurate, { outputName, bundle });
+              return {
+                success: true,
+                editedVideoUrl: editedUrl,
+                segmentUrls: urls,
+                originalFilename,
+                timings,
+                attempts,
+                message: join
+                  ? `Joined ${ranges.length} ranges into one video`
+                  : `Extracted ${ranges.length} clip${ranges.length === 1 ? '' : 's'}${bundle ? ', bundled as a zip' : ''}`
+              };
+            } catch (error) {
+              console.error("Failed to extract clips:", error);
+              return {
+                success: false,
+                error: toolError(error, "Failed to extract clips"),
+              };
+            }
+          }
+          
+          if (startTime === undefined || endTime === undefined) {
+            return {
+              success: false,
+              error: "Give startTime and endTime, or a list of ranges",
+            };
+          }
+          
           try {
-            const editedUrl = await videoProcessor.trimVideo(videoUrl, startTime, endTime);
+            const { url: editedUrl, originalFilename, timings, attempts } = await videoProcessor.trimVideo(videoUrl, startTime, endTime, accurate, { outputName });
+            const duration = typeof startTime === 'number' && typeof endTime === 'number'
+              ? endTime - startTime
+              : undefined;
             return {
               success: true,
               editedVideoUrl: editedUrl,
-              duration: endTime - startTime,
-              message: `Video trimmed from ${startTime}s to ${endTime}s (${endTime - startTime}s duration)`
+              originalFilename,
+              timings,
+              attempts,
+              duration,
+              message: duration !== undefined
+                ? `Video trimmed from ${startTime}s to ${endTime}s (${duration}s duration)`
+                : `Video trimmed from ${startTime} to ${endTime}`
             };
           } catch (error) {
             console.error("Failed to trim video:", error);
             return {
               success: false,
-              error: "Failed to trim video",
+              error: toolError(error, "Failed to trim video"),
             };
           }
         },
       },
       adjustSpeed: {
-        description: "Change the playback speed of the video",
+        description: "Change the playback speed of the video, either uniformly or as a ramp between keyframed speeds (e.g. slow down for a highlight and speed back up)",
         parameters: z.object({
           videoUrl: z.string().describe("URL of the video"),
-          speed: z.number().describe("Playback speed multiplier (0.5 to 2.0)"),
+          speed: z.number().optional().describe("Playback speed multiplier (0.5 to 2.0) for the whole video"),
+          speedPoints: z.array(z.object({
+            time: z.number().min(0).describe("Time in the original video, in seconds"),
+            speed: z.number().min(0.1).max(4).describe("Speed multiplier at that time"),
+          })).min(2).optional().describe("Speed ramp keyframes, e.g. 1.0 at 4s, 0.25 at 5s, 1.0 at 8s; speed eases linearly between them"),
+          muteAudio: z.boolean().optional().describe("With speedPoints, drop the audio instead of time-stretching it"),
+          outputName: outputNameParameter,
         }),
-        execute: async ({ videoUrl, speed }) => {
+        execute: async ({ videoUrl, speed, speedPoints, muteAudio, outputName }) => {
+          if (speedPoints) {
+            try {
+              const { url: editedUrl, originalFilename, timings, attempts } = await videoProcessor.rampSpeed(videoUrl, speedPoints, muteAudio, { outputName });
+              return {
+                success: true,
+                editedVideoUrl: editedUrl,
+                originalFilename,
+                timings,
+                attempts,
+                message: `Applied a speed ramp through ${speedPoints.map((point) => `${point.speed}x at ${point.time}s`).join(', ')}`
+              };
+            } catch (error) {
+              console.error("Failed to ramp video speed:", error);
+              return {
+                success: false,
+                error: toolError(error, "Failed to ramp video speed"),
+              };
+            }
+          }
+          
+          if (speed === undefined) {
+            return {
+              success: false,
+              error: "Give a speed, or speedPoints for a ramp",
+            };
+          }
+          
           try {
-            const editedUrl = await videoProcessor.adjustSpeed(videoUrl, speed);
+            const { url: editedUrl, originalFilename, timings, attempts } = await videoProcessor.adjustSpeed(videoUrl, speed, { outputName });
             return {
               success: true,
               editedVideoUrl: editedUrl,
+              originalFilename,
+              timings,
+              attempts,
               appliedSpeed: speed,
               message: `Video speed adjusted to ${speed}x`
             };
@@ -173,7 +370,7 @@ export async function POST(request: Request) {
             console.error("Failed to adjust video speed:", error);
             return {
               success: false,
-              error: "Failed to adjust video speed",
+              error: toolError(error, "Failed to adjust video speed"),
             };
           }
         },
@@ -183,13 +380,17 @@ export async function POST(request: Request) {
         parameters: z.object({
           videoUrl: z.string().describe("URL of the video"),
           brightness: z.number().describe("Brightness adjustment value (-100 to 100)"),
+          outputName: outputNameParameter,
         }),
-        execute: async ({ videoUrl, brightness }) => {
+        execute: async ({ videoUrl, brightness, outputName }) => {
           try {
-            const editedUrl = await videoProcessor.adjustBrightness(videoUrl, brightness);
+            const { url: editedUrl, originalFilename, timings, attempts } = await videoProcessor.adjustBrightness(videoUrl, brightness, { outputName });
             return {
               success: true,
               editedVideoUrl: editedUrl,
+              originalFilename,
+              timings,
+              attempts,
               appliedBrightness: brightness,
               message: `Video brightness adjusted by ${brightness > 0 ? '+' : ''}${brightness}`
             };
@@ -197,7 +398,7 @@ export async function POST(request: Request) {
             console.error("Failed to adjust brightness:", error);
             return {
               success: false,
-              error: "Failed to adjust brightness",
+              error: toolError(error, "Failed to adjust brightness"),
             };
           }
         },
@@ -210,16 +411,20 @@ export async function POST(request: Request) {
           position: z.enum(["top", "center", "bottom"]).describe("Position of the text"),
           startTime: z.number().optional().describe("Start time in seconds"),
           endTime: z.number().optional().describe("End time in seconds"),
+          outputName: outputNameParameter,
         }),
-        execute: async ({ videoUrl, text, position, startTime, endTime }) => {
+        execute: async ({ videoUrl, text, position, startTime, endTime, outputName }) => {
           try {
-            const editedUrl = await videoProcessor.addText(videoUrl, text, position, startTime, endTime);
+            const { url: editedUrl, originalFilename, timings, attempts } = await videoProcessor.addText(videoUrl, text, position, startTime, endTime, { outputName });
             const timeRange = startTime !== undefined && endTime !== undefined 
               ? ` from ${startTime}s to ${endTime}s` 
               : '';
             return {
               success: true,
               editedVideoUrl: editedUrl,
+              originalFilename,
+              timings,
+              attempts,
               addedText: text,
               message: `Added text "${text}" at ${position} position${timeRange}`
             };
@@ -227,7 +432,7 @@ export async function POST(request: Request) {
             console.error("Failed to add text:", error);
             return {
               success: false,
-              error: "Failed to add text",
+              error: toolError(error, "Failed to add text"),
             };
           }
         },
@@ -240,13 +445,17 @@ export async function POST(request: Request) {
           y: z.number().describe("Y coordinate of crop start"),
           width: z.number().describe("Width of crop"),
           height: z.number().describe("Height of crop"),
+          outputName: outputNameParameter,
         }),
-        execute: async ({ videoUrl, x, y, width, height }) => {
+        execute: async ({ videoUrl, x, y, width, height, outputName }) => {
           try {
-            const editedUrl = await videoProcessor.cropVideo(videoUrl, x, y, width, height);
+            const { url: editedUrl, originalFilename, timings, attempts } = await videoProcessor.cropVideo(videoUrl, x, y, width, height, { outputName });
             return {
               success: true,
               editedVideoUrl: editedUrl,
+              originalFilename,
+              timings,
+              attempts,
               cropDimensions: { x, y, width, height },
               message: `Video cropped to ${width}x${height} starting at (${x}, ${y})`
             };
@@ -254,31 +463,39 @@ export async function POST(request: Request) {
             console.error("Failed to crop video:", error);
             return {
               success: false,
-              error: "Failed to crop video",
+              error: toolError(error, "Failed to crop video"),
             };
           }
         },
       },
       rotateVideo: {
-        description: "Rotate the video by specified degrees",
+        description: "Rotate the video clockwise and/or flip it horizontally or vertically",
         parameters: z.object({
           videoUrl: z.string().describe("URL of the video"),
-          degrees: z.number().describe("Degrees to rotate (90, 180, 270)"),
+          degrees: z.number().describe("Degrees to rotate clockwise (0, 90, 180, 270); use 0 to only flip"),
+          flip: z.enum(["horizontal", "vertical"]).optional().describe("Mirror the frame left-right (horizontal) or top-bottom (vertical)"),
+          metadataOnly: z.boolean().optional().describe("Only change the rotation flag players read, without re-encoding (MP4/MOV only; faster and lossless)"),
+          outputName: outputNameParameter,
         }),
-        execute: async ({ videoUrl, degrees }) => {
+        execute: async ({ videoUrl, degrees, flip, metadataOnly, outputName }) => {
           try {
-            const editedUrl = await videoProcessor.rotateVideo(videoUrl, degrees);
+            const { url: editedUrl, originalFilename, timings, attempts } = await videoProcessor.rotateVideo(videoUrl, degrees, flip, metadataOnly, { outputName });
             return {
               success: true,
               editedVideoUrl: editedUrl,
+              originalFilename,
+              timings,
+              attempts,
               rotationDegrees: degrees,
-              message: `Video rotated ${degrees} degrees`
+              message: flip
+                ? `Video rotated ${degrees} degrees and flipped ${flip}ly`
+                : `Video rotated ${degrees} degrees`
             };
           } catch (error) {
             console.error("Failed to rotate video:", error);
             return {
               success: false,
-              error: "Failed to rotate video",
+              error: toolError(error, "Failed to rotate video"),
             };
           }
         },
@@ -288,13 +505,17 @@ export async function POST(request: Request) {
         parameters: z.object({
           videoUrl: z.string().describe("URL of the video"),
           volume: z.number().describe("Volume multiplier (0.0 to 2.0)"),
+          outputName: outputNameParameter,
         }),
-        execute: async ({ videoUrl, volume }) => {
+        execute: async ({ videoUrl, volume, outputName }) => {
           try {
-            const editedUrl = await videoProcessor.adjustVolume(videoUrl, volume);
+            const { url: editedUrl, originalFilename, timings, attempts } = await videoProcessor.adjustVolume(videoUrl, volume, { outputName });
             return {
               success: true,
               editedVideoUrl: editedUrl,
+              originalFilename,
+              timings,
+              attempts,
               appliedVolume: volume,
               message: `Volume adjusted to ${Math.round(volume * 100)}%`
             };
@@ -302,24 +523,28 @@ export async function POST(request: Request) {
             console.error("Failed to adjust volume:", error);
             return {
               success: false,
-              error: "Failed to adjust volume",
+              error: toolError(error, "Failed to adjust volume"),
             };
           }
         },
       },
       applyFilter: {
-        description: "Apply a basic filter to the video",
+        description: "Apply a one-word look to the video (for specific color changes use colorAdjust)",
         parameters: z.object({
           videoUrl: z.string().describe("URL of the video"),
-          filter: z.enum(["grayscale", "sepia", "blur", "sharpen"]).describe("Filter to apply"),
+          filter: z.enum(["grayscale", "sepia", "blur", "sharpen", "cinematic"]).describe("Filter to apply"),
           intensity: z.number().optional().describe("Filter intensity (0.0 to 1.0)"),
+          outputName: outputNameParameter,
         }),
-        execute: async ({ videoUrl, filter, intensity = 1 }) => {
+        execute: async ({ videoUrl, filter, intensity = 1, outputName }) => {
           try {
-            const editedUrl = await videoProcessor.applyFilter(videoUrl, filter, intensity);
+            const { url: editedUrl, originalFilename, timings, attempts } = await videoProcessor.applyFilter(videoUrl, filter, intensity, { outputName });
             return {
               success: true,
               editedVideoUrl: editedUrl,
+              originalFilename,
+              timings,
+              attempts,
               appliedFilter: filter,
               appliedIntensity: intensity,
               message: `Applied ${filter} filter with ${Math.round(intensity * 100)}% intensity`
@@ -328,7 +553,932 @@ export async function POST(request: Request) {
             console.error("Failed to apply filter:", error);
             return {
               success: false,
-              error: "Failed to apply filter",
+              error: toolError(error, "Failed to apply filter"),
+            };
+          }
+        },
+      },
+      resizeMedia: {
+        description: "Resize a video or image to the given dimensions",
+        parameters: z.object({
+          mediaUrl: z.string().describe("URL of the video or image"),
+          width: z.number().describe("Target width in pixels, or -2 to keep the aspect ratio"),
+          height: z.number().describe("Target height in pixels, or -2 to keep the aspect ratio"),
+          outputName: outputNameParameter,
+        }),
+        execute: async ({ mediaUrl, width, height, outputName }) => {
+          try {
+            const { url: editedUrl, originalFilename, timings, attempts } = await videoProcessor.resizeMedia(mediaUrl, width, height, { outputName });
+            return {
+              success: true,
+              editedVideoUrl: editedUrl,
+              originalFilename,
+              timings,
+              attempts,
+              message: `Resized to ${width === -2 ? 'auto' : width}x${height === -2 ? 'auto' : height}`
+            };
+          } catch (error) {
+            console.error("Failed to resize media:", error);
+            return {
+              success: false,
+              error: toolError(error, "Failed to resize media"),
+            };
+          }
+        },
+      },
+      convertImage: {
+        description: "Convert an image to another format",
+        parameters: z.object({
+          imageUrl: z.string().describe("URL of the image"),
+          format: z.enum(["jpg", "png", "webp"]).describe("Target image format"),
+          outputName: outputNameParameter,
+        }),
+        execute: async ({ imageUrl, format, outputName }) => {
+          try {
+            const { url: editedUrl, originalFilename, timings, attempts } = await videoProcessor.convertImage(imageUrl, format, { outputName });
+            return {
+              success: true,
+              editedVideoUrl: editedUrl,
+              originalFilename,
+              timings,
+              attempts,
+              message: `Image converted to ${format.toUpperCase()}`
+            };
+          } catch (error) {
+            console.error("Failed to convert image:", error);
+            return {
+              success: false,
+              error: toolError(error, "Failed to convert image"),
+            };
+          }
+        },
+      },
+      normalizeAudio: {
+        description: "Normalize the loudness of a video's or audio file's sound",
+        parameters: z.object({
+          mediaUrl: z.string().describe("URL of the video or audio file"),
+          targetLufs: z.number().optional().describe("Target integrated loudness in LUFS (default -16, use -14 for streaming platforms)"),
+          outputName: outputNameParameter,
+        }),
+        execute: async ({ mediaUrl, targetLufs = -16, outputName }) => {
+          try {
+            const { url: editedUrl, originalFilename, timings, attempts } = await videoProcessor.normalizeAudio(mediaUrl, targetLufs, { outputName });
+            return {
+              success: true,
+              editedVideoUrl: editedUrl,
+              originalFilename,
+              timings,
+              attempts,
+              message: `Audio normalized to ${targetLufs} LUFS`
+            };
+          } catch (error) {
+            console.error("Failed to normalize audio:", error);
+            return {
+              success: false,
+              error: toolError(error, "Failed to normalize audio"),
+            };
+          }
+        },
+      },
+      fadeAudio: {
+        description: "Fade the sound of a video or audio file in at the start and/or out at the end",
+        parameters: z.object({
+          mediaUrl: z.string().describe("URL of the video or audio file"),
+          fadeIn: z.number().describe("Fade-in duration in seconds (0 for none)"),
+          fadeOut: z.number().describe("Fade-out duration in seconds (0 for none)"),
+          outputName: outputNameParameter,
+        }),
+        execute: async ({ mediaUrl, fadeIn, fadeOut, outputName }) => {
+          try {
+            const { url: editedUrl, originalFilename, timings, attempts } = await videoProcessor.fadeAudio(mediaUrl, fadeIn, fadeOut, { outputName });
+            return {
+              success: true,
+              editedVideoUrl: editedUrl,
+              originalFilename,
+              timings,
+              attempts,
+              message: `Added a ${fadeIn}s fade-in and ${fadeOut}s fade-out`
+            };
+          } catch (error) {
+            console.error("Failed to fade audio:", error);
+            return {
+              success: false,
+              error: toolError(error, "Failed to fade audio"),
+            };
+          }
+        },
+      },
+      transcodeAudio: {
+        description: "Convert an audio file to another format, or extract the audio track of a video",
+        parameters: z.object({
+          mediaUrl: z.string().describe("URL of the video or audio file"),
+          format: z.enum(["mp3", "wav", "m4a"]).describe("Target audio format"),
+          outputName: outputNameParameter,
+        }),
+        execute: async ({ mediaUrl, format, outputName }) => {
+          try {
+            const { url: editedUrl, originalFilename, timings, attempts } = await videoProcessor.transcodeAudio(mediaUrl, format, { outputName });
+            return {
+              success: true,
+              editedVideoUrl: editedUrl,
+              originalFilename,
+              timings,
+              attempts,
+              message: `Audio converted to ${format.toUpperCase()}`
+            };
+          } catch (error) {
+            console.error("Failed to transcode audio:", error);
+            return {
+              success: false,
+              error: toolError(error, "Failed to transcode audio"),
+            };
+          }
+        },
+      },
+      renderWaveform: {
+        description: "Render a PNG image of the waveform of a video's or audio file's sound",
+        parameters: z.object({
+          mediaUrl: z.string().describe("URL of the video or audio file"),
+          width: z.number().optional().describe("Image width in pixels (default 1280)"),
+          height: z.number().optional().describe("Image height in pixels (default 240)"),
+          outputName: outputNameParameter,
+        }),
+        execute: async ({ mediaUrl, width = 1280, height = 240, outputName }) => {
+          try {
+            const { url: editedUrl, originalFilename, timings, attempts } = await videoProcessor.renderWaveform(mediaUrl, width, height, { outputName });
+            return {
+              success: true,
+              editedVideoUrl: editedUrl,
+              originalFilename,
+              timings,
+              attempts,
+              message: `Rendered a ${width}x${height} waveform`
+            };
+          } catch (error) {
+            console.error("Failed to render waveform:", error);
+            return {
+              success: false,
+              error: toolError(error, "Failed to render waveform"),
+            };
+          }
+        },
+      },
+      setChapters: {
+        description: "Add chapter markers to a video or audio file so players can jump between sections",
+        parameters: z.object({
+          mediaUrl: z.string().describe("URL of the video or audio file"),
+          chapters: z.array(z.object({
+            start: z.number().describe("Chapter start time in seconds"),
+            title: z.string().describe("Chapter title"),
+          })).min(1).describe("Chapters in playback order"),
+          outputName: outputNameParameter,
+        }),
+        execute: async ({ mediaUrl, chapters, outputName }) => {
+          try {
+            const { url: editedUrl, originalFilename, timings, attempts } = await videoProcessor.setChapters(mediaUrl, chapters, { outputName });
+            return {
+              success: true,
+              editedVideoUrl: editedUrl,
+              originalFilename,
+              timings,
+              attempts,
+              message: `Added ${chapters.length} chapter${chapters.length === 1 ? '' : 's'}`
+            };
+          } catch (error) {
+            console.error("Failed to set chapters:", error);
+            return {
+              success: false,
+              error: toolError(error, "Failed to set chapters"),
+            };
+          }
+        },
+      },
+      replaceAudio: {
+        description: "Replace a video's soundtrack with the audio from another file, such as uploaded music or a voiceover",
+        parameters: z.object({
+          videoUrl: z.string().describe("URL of the video to keep the picture from"),
+          audioUrl: z.string().describe("URL of the audio (or video) file to take the sound from"),
+          outputName: outputNameParameter,
+        }),
+        execute: async ({ videoUrl, audioUrl, outputName }) => {
+          try {
+            const { url: editedUrl, originalFilename, timings, attempts } = await videoProcessor.replaceAudio(videoUrl, audioUrl, { outputName });
+            return {
+              success: true,
+              editedVideoUrl: editedUrl,
+              originalFilename,
+              timings,
+              attempts,
+              message: "Replaced the video's audio track"
+            };
+          } catch (error) {
+            console.error("Failed to replace audio:", error);
+            return {
+              success: false,
+              error: toolError(error, "Failed to replace audio"),
+            };
+          }
+        },
+      },
+      createSlideshow: {
+        description: "Turn one or more images into a video slideshow, showing each image for a set time",
+        parameters: z.object({
+          imageUrls: z.array(z.string()).min(1).describe("URLs of the images, in the order they should appear"),
+          secondsPerImage: z.number().positive().optional().describe("How long each image is shown, in seconds (default 3)"),
+          width: z.number().int().positive().optional().describe("Video width in pixels (default 1920)"),
+          height: z.number().int().positive().optional().describe("Video height in pixels (default 1080)"),
+          outputName: outputNameParameter,
+        }),
+        execute: async ({ imageUrls, secondsPerImage, width, height, outputName }) => {
+          try {
+            const { url: editedUrl, originalFilename, timings, attempts } = await videoProcessor.createSlideshow(imageUrls, secondsPerImage, width, height, { outputName });
+            return {
+              success: true,
+              editedVideoUrl: editedUrl,
+              originalFilename,
+              timings,
+              attempts,
+              message: `Created a slideshow from ${imageUrls.length} image${imageUrls.length === 1 ? '' : 's'}`
+            };
+          } catch (error) {
+            console.error("Failed to create slideshow:", error);
+            return {
+              success: false,
+              error: toolError(error, "Failed to create slideshow"),
+            };
+          }
+        },
+      },
+      reframe: {
+        description: "Convert a video or image to another aspect ratio (e.g. vertical 9:16 for Reels/TikTok/Shorts), keeping the whole frame and filling the edges with a blurred copy of it",
+        parameters: z.object({
+          videoUrl: z.string().describe("URL of the video or image"),
+          aspectRatio: z.enum(["9:16", "1:1", "4:5", "16:9"]).describe("Target aspect ratio"),
+          outputName: outputNameParameter,
+        }),
+        execute: async ({ videoUrl, aspectRatio, outputName }) => {
+          try {
+            const { url: editedUrl, originalFilename, timings, attempts } = await videoProcessor.reframe(videoUrl, aspectRatio, { outputName });
+            return {
+              success: true,
+              editedVideoUrl: editedUrl,
+              originalFilename,
+              timings,
+              attempts,
+              message: `Reframed to ${aspectRatio} with a blurred background`
+            };
+          } catch (error) {
+            console.error("Failed to reframe video:", error);
+            return {
+              success: false,
+              error: toolError(error, "Failed to reframe video"),
+            };
+          }
+        },
+      },
+      addWatermark: {
+        description: "Overlay an uploaded logo image (PNG, JPG, or WebP; SVG logos must be exported to PNG first) on a video or image, e.g. a brand watermark in a corner",
+        parameters: z.object({
+          videoUrl: z.string().describe("URL of the video or image to watermark"),
+          logoUrl: z.string().describe("URL of the logo image"),
+          ...overlayPlacementParameters,
+          startTime: z.number().optional().describe("Show the logo from this time in seconds"),
+          endTime: z.number().optional().describe("Hide the logo after this time in seconds"),
+          outputName: outputNameParameter,
+        }),
+        execute: async ({ videoUrl, logoUrl, outputName, ...placement }) => {
+          try {
+            const { url: editedUrl, originalFilename, timings, attempts } = await videoProcessor.addWatermark(videoUrl, logoUrl, placement, { outputName });
+            return {
+              success: true,
+              editedVideoUrl: editedUrl,
+              originalFilename,
+              timings,
+              attempts,
+              message: `Added watermark at ${placement.x !== undefined || placement.y !== undefined ? 'a custom position' : placement.position ?? 'bottom-right'}`
+            };
+          } catch (error) {
+            console.error("Failed to add watermark:", error);
+            return {
+              success: false,
+              error: toolError(error, "Failed to add watermark"),
+            };
+          }
+        },
+      },
+      addAnimatedOverlay: {
+        description: "Play an uploaded animation (GIF, animated PNG, or transparent WebM) on top of the video from a given time, e.g. an animated 'subscribe' button at 5 seconds",
+        parameters: z.object({
+          videoUrl: z.string().describe("URL of the video"),
+          overlayUrl: z.string().describe("URL of the animated overlay"),
+          startTime: z.number().min(0).optional().describe("When the animation starts, in seconds (default 0)"),
+          loop: z.boolean().optional().describe("Repeat the animation until the video ends instead of playing it once"),
+          ...overlayPlacementParameters,
+          endTime: z.number().optional().describe("Hide the overlay after this time in seconds"),
+          outputName: outputNameParameter,
+        }),
+        execute: async ({ videoUrl, overlayUrl, startTime, loop, outputName, ...placement }) => {
+          try {
+            const { url: editedUrl, originalFilename, timings, attempts } = await videoProcessor.addAnimatedOverlay(videoUrl, overlayUrl, startTime, loop, placement, { outputName });
+            return {
+              success: true,
+              editedVideoUrl: editedUrl,
+              originalFilename,
+              timings,
+              attempts,
+              message: `Added animated overlay from ${startTime ?? 0}s${loop ? ', looping' : ''}`
+            };
+          } catch (error) {
+            console.error("Failed to add animated overlay:", error);
+            return {
+              success: false,
+              error: toolError(error, "Failed to add animated overlay"),
+            };
+          }
+        },
+      },
+      composeGrid: {
+        description: "Combine 2 to 4 videos into one frame: side by side, stacked top to bottom, or a 2x2 grid. Good for comparisons and multi-camera views",
+        parameters: z.object({
+          videoUrls: z.array(z.string()).min(2).max(4).describe("URLs of the videos, in reading order"),
+          layout: z.enum(["side-by-side", "stacked", "grid"]).describe("Arrangement; grid is 2x2"),
+          audio: z.union([z.literal("mix"), z.number().int().min(0).max(3)]).optional().describe("Index of the video to take sound from (default 0), or 'mix' to blend all of them"),
+          cellWidth: z.number().int().positive().optional().describe("Width of each video's cell in pixels (default 960)"),
+          cellHeight: z.number().int().positive().optional().describe("Height of each video's cell in pixels (default 540)"),
+          outputName: outputNameParameter,
+        }),
+        execute: async ({ videoUrls, layout, audio, cellWidth, cellHeight, outputName }) => {
+          try {
+            const { url: editedUrl, originalFilename, timings, attempts } = await videoProcessor.composeGrid(videoUrls, layout, audio, cellWidth, cellHeight, { outputName });
+            return {
+              success: true,
+              editedVideoUrl: editedUrl,
+              originalFilename,
+              timings,
+              attempts,
+              message: `Combined ${videoUrls.length} videos (${layout})`
+            };
+          } catch (error) {
+            console.error("Failed to compose videos:", error);
+            return {
+              success: false,
+              error: toolError(error, "Failed to compose videos"),
+            };
+          }
+        },
+      },
+      concatVideos: {
+        description: "Join several videos end to end into one, in the given order, with hard cuts or transitions between them",
+        parameters: z.object({
+          videoUrls: z.array(z.string()).min(2).describe("URLs of the clips, in playback order"),
+          transitions: z.array(z.object({
+            style: z.enum(["fade", "dissolve", "wipe", "slide", "fade-to-black"]).describe("Transition style"),
+            duration: z.number().positive().describe("Transition length in seconds, e.g. 1"),
+          })).optional().describe("One transition per junction between clips, or a single one used for all; omit for hard cuts"),
+          outputName: outputNameParameter,
+        }),
+        execute: async ({ videoUrls, transitions, outputName }) => {
+          try {
+            const { url: editedUrl, originalFilename, timings, attempts } = await videoProcessor.concatVideos(videoUrls, transitions, { outputName });
+            return {
+              success: true,
+              editedVideoUrl: editedUrl,
+              originalFilename,
+              timings,
+              attempts,
+              message: `Joined ${videoUrls.length} clips${transitions?.length ? ` with ${transitions[0].style} transitions` : ''}`
+            };
+          } catch (error) {
+            console.error("Failed to join videos:", error);
+            return {
+              success: false,
+              error: toolError(error, "Failed to join videos"),
+            };
+          }
+        },
+      },
+      splitVideo: {
+        description: "Cut a video or audio file into several consecutive parts: every N seconds, at given timestamps, or into K equal parts. Returns one URL per part, or a single zip when bundled",
+        parameters: z.object({
+          videoUrl: z.string().describe("URL of the video or audio file"),
+          every: z.number().positive().optional().describe("Length of each part in seconds"),
+          at: z.array(z.number().positive()).min(1).optional().describe("Timestamps in seconds to cut at"),
+          parts: z.number().int().min(2).optional().describe("Number of equal parts"),
+          copy: z.boolean().optional().describe("Cut without re-encoding: much faster, but each cut moves to the nearest following keyframe"),
+          bundle: z.boolean().optional().describe("Return all parts as a single zip download instead of a URL per part"),
+          outputName: outputNameParameter,
+        }),
+        execute: async ({ videoUrl, every, at, parts, copy, bundle = false, outputName }) => {
+          const split = every !== undefined ? { every } : at !== undefined ? { at } : parts !== undefined ? { parts } : undefined;
+          if (!split) {
+            return {
+              success: false,
+              error: "Give one of every, at, or parts to split the video",
+            };
+          }
+          
+          try {
+            const { url: editedUrl, urls, originalFilename, timings, attempts } = await videoProcessor.splitVideo(videoUrl, split, copy, { outputName, bundle });
+            return {
+              success: true,
+              editedVideoUrl: editedUrl,
+              segmentUrls: urls,
+              originalFilename,
+              timings,
+              attempts,
+              message: bundle ? "Split into parts, bundled as a zip" : `Split into ${urls?.length ?? 1} parts`
+            };
+          } catch (error) {
+            console.error("Failed to split video:", error);
+            return {
+              success: false,
+              error: toolError(error, "Failed to split video"),
+            };
+          }
+        },
+      },
+      reverseVideo: {
+        description: "Play a video (or audio file) backwards",
+        parameters: z.object({
+          videoUrl: z.string().describe("URL of the video or audio file"),
+          includeAudio: z.boolean().optional().describe("Reverse the sound too (default true); false drops the audio"),
+          outputName: outputNameParameter,
+        }),
+        execute: async ({ videoUrl, includeAudio, outputName }) => {
+          try {
+            const { url: editedUrl, originalFilename, timings, attempts } = await videoProcessor.reverseVideo(videoUrl, includeAudio, { outputName });
+            return {
+              success: true,
+              editedVideoUrl: editedUrl,
+              originalFilename,
+              timings,
+              attempts,
+              message: includeAudio === false ? "Video reversed without audio" : "Video reversed"
+            };
+          } catch (error) {
+            console.error("Failed to reverse video:", error);
+            return {
+              success: false,
+              error: toolError(error, "Failed to reverse video"),
+            };
+          }
+        },
+      },
+      createBoomerang: {
+        description: "Make a boomerang: a short moment played forwards then backwards on repeat",
+        parameters: z.object({
+          videoUrl: z.string().describe("URL of the video"),
+          startTime: z.number().min(0).describe("Start of the moment in seconds"),
+          endTime: z.number().positive().describe("End of the moment in seconds (at most 10 seconds after the start)"),
+          repetitions: z.number().int().min(1).max(10).optional().describe("How many forward-and-back cycles (default 3)"),
+          outputName: outputNameParameter,
+        }),
+        execute: async ({ videoUrl, startTime, endTime, repetitions, outputName }) => {
+          try {
+            const { url: editedUrl, originalFilename, timings, attempts } = await videoProcessor.createBoomerang(videoUrl, startTime, endTime, repetitions, { outputName });
+            return {
+              success: true,
+              editedVideoUrl: editedUrl,
+              originalFilename,
+              timings,
+              attempts,
+              message: `Created a boomerang from ${startTime}s to ${endTime}s`
+            };
+          } catch (error) {
+            console.error("Failed to create boomerang:", error);
+            return {
+              success: false,
+              error: toolError(error, "Failed to create boomerang"),
+            };
+          }
+        },
+      },
+      freezeFrame: {
+        description: "Pause the picture on one frame for a few seconds (e.g. to annotate a moment), then continue playing",
+        parameters: z.object({
+          videoUrl: z.string().describe("URL of the video"),
+          atTime: z.number().positive().describe("Time in seconds of the frame to hold"),
+          holdSeconds: z.number().positive().describe("How long to hold it, in seconds"),
+          outputName: outputNameParameter,
+        }),
+        execute: async ({ videoUrl, atTime, holdSeconds, outputName }) => {
+          try {
+            const { url: editedUrl, originalFilename, timings, attempts } = await videoProcessor.freezeFrame(videoUrl, atTime, holdSeconds, { outputName });
+            return {
+              success: true,
+              editedVideoUrl: editedUrl,
+              originalFilename,
+              timings,
+              attempts,
+              message: `Froze the frame at ${atTime}s for ${holdSeconds}s`
+            };
+          } catch (error) {
+            console.error("Failed to freeze frame:", error);
+            return {
+              success: false,
+              error: toolError(error, "Failed to freeze frame"),
+            };
+          }
+        },
+      },
+      slowMotion: {
+        description: "Smooth slow motion with interpolated in-between frames, for slowing below 0.5x without stutter (slower to process than adjustSpeed)",
+        parameters: z.object({
+          videoUrl: z.string().describe("URL of the video"),
+          speed: z.number().min(0.05).max(0.9).describe("Playback speed multiplier, e.g. 0.25 for quarter speed"),
+          quality: z.enum(["fast", "balanced", "high"]).optional().describe("Interpolation quality: fast blends frames, balanced and high estimate motion (default balanced)"),
+          muteAudio: z.boolean().optional().describe("Drop the audio instead of stretching it"),
+          outputName: outputNameParameter,
+        }),
+        execute: async ({ videoUrl, speed, quality, muteAudio, outputName }) => {
+          try {
+            const { url: editedUrl, originalFilename, timings, attempts } = await videoProcessor.slowMotion(videoUrl, speed, quality, muteAudio, { outputName });
+            return {
+              success: true,
+              editedVideoUrl: editedUrl,
+              originalFilename,
+              timings,
+              attempts,
+              message: `Slowed the video to ${speed}x with interpolated frames`
+            };
+          } catch (error) {
+            console.error("Failed to create slow motion:", error);
+            return {
+              success: false,
+              error: toolError(error, "Failed to create slow motion"),
+            };
+          }
+        },
+      },
+      changeFps: {
+        description: "Convert the video to a different frame rate without changing its speed, e.g. 60fps to 30fps, or the cinematic 24fps look",
+        parameters: z.object({
+          videoUrl: z.string().describe("URL of the video"),
+          fps: z.number().min(1).max(120).optional().describe("Target frames per second"),
+          preset: z.enum(["cinematic"]).optional().describe("Named frame rate instead of fps: cinematic is 24fps"),
+          method: z.enum(["drop", "interpolate"]).optional().describe("drop drops or repeats frames (fast, default); interpolate creates in-between frames from motion, smoother when raising the rate"),
+          outputName: outputNameParameter,
+        }),
+        execute: async ({ videoUrl, fps, preset, method, outputName }) => {
+          const targetFps = preset ? FPS_PRESETS[preset] : fps;
+          if (targetFps === undefined) {
+            return {
+              success: false,
+              error: "Give an fps or a preset",
+            };
+          }
+          
+          try {
+            const { url: editedUrl, originalFilename, fps: outputFps, timings, attempts } = await videoProcessor.changeFps(videoUrl, targetFps, method, { outputName });
+            return {
+              success: true,
+              editedVideoUrl: editedUrl,
+              originalFilename,
+              fps: outputFps ?? targetFps,
+              timings,
+              attempts,
+              message: `Converted the video to ${targetFps}fps`
+            };
+          } catch (error) {
+            console.error("Failed to change frame rate:", error);
+            return {
+              success: false,
+              error: toolError(error, "Failed to change frame rate"),
+            };
+          }
+        },
+      },
+      deinterlace: {
+        description: "Remove the combing lines from interlaced footage (old broadcast, DV or camcorder video). Interlacing is detected automatically",
+        parameters: z.object({
+          videoUrl: z.string().describe("URL of the video"),
+          algorithm: z.enum(["bwdif", "yadif"]).optional().describe("bwdif is sharper (default), yadif is faster"),
+          force: z.boolean().optional().describe("Deinterlace every frame even if the file doesn't say it is interlaced"),
+          outputName: outputNameParameter,
+        }),
+        execute: async ({ videoUrl, algorithm, force, outputName }) => {
+          try {
+            const { url: editedUrl, originalFilename, timings, attempts } = await videoProcessor.deinterlace(videoUrl, algorithm, force, { outputName });
+            return {
+              success: true,
+              editedVideoUrl: editedUrl,
+              originalFilename,
+              timings,
+              attempts,
+              message: "Deinterlaced the video"
+            };
+          } catch (error) {
+            console.error("Failed to deinterlace video:", error);
+            return {
+              success: false,
+              error: toolError(error, "Failed to deinterlace video"),
+            };
+          }
+        },
+      },
+      denoise: {
+        description: "Reduce noise and grain, e.g. in low-light phone footage. Apply before color or brightness adjustments",
+        parameters: z.object({
+          videoUrl: z.string().describe("URL of the video or image"),
+          strength: z.enum(["light", "medium", "strong"]).optional().describe("How much noise to remove (default medium)"),
+          algorithm: z.enum(["hqdn3d", "nlmeans"]).optional().describe("hqdn3d is fast (default); nlmeans keeps more detail but is much slower"),
+          outputName: outputNameParameter,
+        }),
+        execute: async ({ videoUrl, strength, algorithm, outputName }) => {
+          try {
+            const { url: editedUrl, originalFilename, timings, attempts } = await videoProcessor.denoise(videoUrl, strength, algorithm, { outputName });
+            return {
+              success: true,
+              editedVideoUrl: editedUrl,
+              originalFilename,
+              timings,
+              attempts,
+              message: `Applied ${strength ?? "medium"} noise reduction`
+            };
+          } catch (error) {
+            console.error("Failed to denoise video:", error);
+            return {
+              success: false,
+              error: toolError(error, "Failed to denoise video"),
+            };
+          }
+        },
+      },
+      sharpen: {
+        description: "Sharpen soft or slightly out-of-focus video or images. Strength is adjusted to the resolution automatically",
+        parameters: z.object({
+          videoUrl: z.string().describe("URL of the video or image"),
+          amount: z.number().min(0.1).max(3).optional().describe("Sharpening amount (default 1; above 1.5 is strong)"),
+          radius: z.number().min(1).max(10).optional().describe("Size of the detail to sharpen, in pixels at 1080p (default 2)"),
+          outputName: outputNameParameter,
+        }),
+        execute: async ({ videoUrl, amount, radius, outputName }) => {
+          try {
+            const { url: editedUrl, originalFilename, timings, attempts } = await videoProcessor.sharpen(videoUrl, amount, radius, { outputName });
+            return {
+              success: true,
+              editedVideoUrl: editedUrl,
+              originalFilename,
+              timings,
+              attempts,
+              message: `Sharpened with amount ${amount ?? 1}`
+            };
+          } catch (error) {
+            console.error("Failed to sharpen video:", error);
+            return {
+              success: false,
+              error: toolError(error, "Failed to sharpen video"),
+            };
+          }
+        },
+      },
+      stabilize: {
+        description: "Smooth out camera shake in handheld footage. Takes roughly twice as long as other edits",
+        parameters: z.object({
+          videoUrl: z.string().describe("URL of the video"),
+          shakiness: z.number().int().min(1).max(10).optional().describe("How shaky the footage is, 1 (slightly) to 10 (very) (default 5)"),
+          smoothing: z.number().int().min(1).max(100).optional().describe("Frames either side averaged into the camera path; higher is steadier but less responsive to intended pans (default 10)"),
+          outputName: outputNameParameter,
+        }),
+        execute: async ({ videoUrl, shakiness, smoothing, outputName }) => {
+          try {
+            const { url: editedUrl, originalFilename, timings, attempts } = await videoProcessor.stabilize(videoUrl, shakiness, smoothing, { outputName });
+            return {
+              success: true,
+              editedVideoUrl: editedUrl,
+              originalFilename,
+              timings,
+              attempts,
+              message: "Stabilized the video"
+            };
+          } catch (error) {
+            console.error("Failed to stabilize video:", error);
+            return {
+              success: false,
+              error: toolError(error, "Failed to stabilize video"),
+            };
+          }
+        },
+      },
+      addVignette: {
+        description: "Darken the edges and corners of the frame to focus attention on the center",
+        parameters: z.object({
+          videoUrl: z.string().describe("URL of the video or image"),
+          intensity: z.number().min(0).max(1).optional().describe("How strong the vignette is, 0 to 1 (default 0.5)"),
+          outputName: outputNameParameter,
+        }),
+        execute: async ({ videoUrl, intensity = 0.5, outputName }) => {
+          try {
+            const { url: editedUrl, originalFilename, timings, attempts } = await videoProcessor.addVignette(videoUrl, intensity, { outputName });
+            return {
+              success: true,
+              editedVideoUrl: editedUrl,
+              originalFilename,
+              timings,
+              attempts,
+              message: `Added a vignette at ${Math.round(intensity * 100)}% intensity`
+            };
+          } catch (error) {
+            console.error("Failed to add vignette:", error);
+            return {
+              success: false,
+              error: toolError(error, "Failed to add vignette"),
+            };
+          }
+        },
+      },
+      addGrain: {
+        description: "Add moving film grain for a filmic or vintage look",
+        parameters: z.object({
+          videoUrl: z.string().describe("URL of the video"),
+          intensity: z.number().min(0).max(1).optional().describe("How much grain, 0 to 1 (default 0.3)"),
+          outputName: outputNameParameter,
+        }),
+        execute: async ({ videoUrl, intensity = 0.3, outputName }) => {
+          try {
+            const { url: editedUrl, originalFilename, timings, attempts } = await videoProcessor.addGrain(videoUrl, intensity, { outputName });
+            return {
+              success: true,
+              editedVideoUrl: editedUrl,
+              originalFilename,
+              timings,
+              attempts,
+              message: `Added film grain at ${Math.round(intensity * 100)}% intensity`
+            };
+          } catch (error) {
+            console.error("Failed to add grain:", error);
+            return {
+              success: false,
+              error: toolError(error, "Failed to add grain"),
+            };
+          }
+        },
+      },
+      obscureRegion: {
+        description: "Blur or pixelate a rectangular area, e.g. to hide a license plate, a screen or a bystander's face, for the whole video or a time range",
+        parameters: z.object({
+          videoUrl: z.string().describe("URL of the video or image"),
+          x: z.number().int().min(0).describe("Left edge of the area in pixels"),
+          y: z.number().int().min(0).describe("Top edge of the area in pixels"),
+          width: z.number().int().min(8).describe("Width of the area in pixels"),
+          height: z.number().int().min(8).describe("Height of the area in pixels"),
+          style: z.enum(["blur", "pixelate"]).optional().describe("How to hide it (default blur)"),
+          startTime: z.number().min(0).optional().describe("Start hiding at this time in seconds (default the beginning)"),
+          endTime: z.number().positive().optional().describe("Stop hiding at this time in seconds (default the end)"),
+          outputName: outputNameParameter,
+        }),
+        execute: async ({ videoUrl, x, y, width, height, style = "blur", startTime, endTime, outputName }) => {
+          try {
+            const { url: editedUrl, originalFilename, timings, attempts } = await videoProcessor.obscureRegion(videoUrl, { x, y, width, height }, style, startTime, endTime, { outputName });
+            return {
+              success: true,
+              editedVideoUrl: editedUrl,
+              originalFilename,
+              timings,
+              attempts,
+              message: `Applied ${style} to a ${width}x${height} area at (${x}, ${y})`
+            };
+          } catch (error) {
+            console.error("Failed to obscure region:", error);
+            return {
+              success: false,
+              error: toolError(error, "Failed to obscure region"),
+            };
+          }
+        },
+      },
+      chromaKey: {
+        description: "Remove a green (or other solid color) screen from the video and place the subject over an uploaded background image or video",
+        parameters: z.object({
+          videoUrl: z.string().describe("URL of the video shot against the colored screen"),
+          backgroundUrl: z.string().describe("URL of the background image or video"),
+          color: z.string().regex(/^(0x|#)[0-9a-fA-F]{6}$/).optional().describe("Screen color as hex, e.g. '#00FF00' for green or '#0000FF' for blue (default green)"),
+          similarity: z.number().min(0.01).max(1).optional().describe("How far from the exact color still counts as screen; raise it if patches of the screen remain (default 0.3)"),
+          blend: z.number().min(0).max(1).optional().describe("Edge softness; raise it if the subject's outline looks jagged (default 0.1)"),
+          outputName: outputNameParameter,
+        }),
+        execute: async ({ videoUrl, backgroundUrl, color, similarity, blend, outputName }) => {
+          try {
+            const { url: editedUrl, originalFilename, timings, attempts } = await videoProcessor.chromaKey(videoUrl, backgroundUrl, color?.replace("#", "0x"), similarity, blend, { outputName });
+            return {
+              success: true,
+              editedVideoUrl: editedUrl,
+              originalFilename,
+              timings,
+              attempts,
+              message: "Replaced the screen with the new background"
+            };
+          } catch (error) {
+            console.error("Failed to apply chroma key:", error);
+            return {
+              success: false,
+              error: toolError(error, "Failed to apply chroma key"),
+            };
+          }
+        },
+      },
+      applyLut: {
+        description: "Color grade a video or image with a .cube 3D LUT (e.g. a team's house grade). The LUT must have been uploaded via /api/files/lut or be a public URL to a .cube file",
+        parameters: z.object({
+          videoUrl: z.string().describe("URL of the video or image"),
+          lutUrl: z.string().describe("URL of the .cube LUT file"),
+          outputName: outputNameParameter,
+        }),
+        execute: async ({ videoUrl, lutUrl, outputName }) => {
+          try {
+            const { url: editedUrl, originalFilename, timings, attempts } = await videoProcessor.applyLut(videoUrl, lutUrl, { outputName });
+            return {
+              success: true,
+              editedVideoUrl: editedUrl,
+              originalFilename,
+              timings,
+              attempts,
+              message: "Applied the LUT grade"
+            };
+          } catch (error) {
+            console.error("Failed to apply LUT:", error);
+            return {
+              success: false,
+              error: toolError(error, "Failed to apply LUT"),
+            };
+          }
+        },
+      },
+      colorAdjust: {
+        description: "Fine-tune color with numeric settings: contrast, brightness, saturation, gamma, hue, shadows and highlights. Give only the settings to change",
+        parameters: z.object({
+          videoUrl: z.string().describe("URL of the video or image"),
+          contrast: z.number().min(0).max(2).optional().describe("0 (flat) to 2; 1 is unchanged"),
+          brightness: z.number().min(-1).max(1).optional().describe("-1 (black) to 1 (white); 0 is unchanged, small steps like 0.05 are noticeable"),
+          saturation: z.number().min(0).max(3).optional().describe("0 (black and white) to 3; 1 is unchanged"),
+          gamma: z.number().min(0.1).max(10).optional().describe("0.1 to 10; 1 is unchanged, above 1 brightens midtones"),
+          hue: z.number().min(-180).max(180).optional().describe("Hue rotation in degrees, -180 to 180"),
+          shadows: z.number().min(-1).max(1).optional().describe("-1 (crush) to 1 (lift) the dark tones; 0 is unchanged"),
+          highlights: z.number().min(-1).max(1).optional().describe("-1 (darken) to 1 (brighten) the bright tones; 0 is unchanged"),
+          outputName: outputNameParameter,
+        }),
+        execute: async ({ videoUrl, outputName, ...adjustments }) => {
+          const changed = Object.entries(adjustments).filter(([, value]) => value !== undefined);
+          if (changed.length === 0) {
+            return {
+              success: false,
+              error: "Give at least one color setting to change",
+            };
+          }
+          
+          try {
+            const { url: editedUrl, originalFilename, timings, attempts } = await videoProcessor.colorAdjust(videoUrl, adjustments, { outputName });
+            return {
+              success: true,
+              editedVideoUrl: editedUrl,
+              originalFilename,
+              timings,
+              attempts,
+              appliedAdjustments: Object.fromEntries(changed),
+              message: `Adjusted ${changed.map(([name, value]) => `${name} to ${value}`).join(', ')}`
+            };
+          } catch (error) {
+            console.error("Failed to adjust color:", error);
+            return {
+              success: false,
+              error: toolError(error, "Failed to adjust color"),
+            };
+          }
+        },
+      },
+      whiteBalance: {
+        description: "Fix a color cast (e.g. orange indoor light or blue shade) automatically, or warm, cool or tint the video by hand",
+        parameters: z.object({
+          videoUrl: z.string().describe("URL of the video or image"),
+          auto: z.boolean().optional().describe("Estimate and remove the color cast from sampled frames"),
+          temperature: z.number().min(1000).max(40000).optional().describe("Color temperature in Kelvin; 6500 is neutral, lower (e.g. 4500) is warmer, higher (e.g. 9000) is cooler"),
+          tint: z.number().min(-1).max(1).optional().describe("-1 (green) to 1 (magenta); 0 is neutral"),
+          outputName: outputNameParameter,
+        }),
+        execute: async ({ videoUrl, auto, temperature, tint, outputName }) => {
+          // 6500K and a zero tint are neutral, so on their own they change nothing
+          if (!auto && (temperature === undefined || temperature === 6500) && !tint) {
+            return {
+              success: false,
+              error: "Nothing to change: give auto, a temperature other than the neutral 6500, or a non-zero tint",
+            };
+          }
+          
+          try {
+            const { url: editedUrl, originalFilename, timings, attempts } = await videoProcessor.whiteBalance(videoUrl, { auto, temperature, tint }, { outputName });
+            return {
+              success: true,
+              editedVideoUrl: editedUrl,
+              originalFilename,
+              timings,
+              attempts,
+              message: auto ? "Corrected the white balance automatically" : "Adjusted the white balance"
+            };
+          } catch (error) {
+            console.error("Failed to adjust white balance:", error);
+            return {
+              success: false,
+              error: toolError(error, "Failed to adjust white balance"),
             };
           }
         },
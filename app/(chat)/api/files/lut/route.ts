@@ -0,0 +1,57 @@
+import { put } from "@vercel/blob";
+import { NextResponse } from "next/server";
+
+import { auth } from "@/app/(auth)/auth";
+import { cubeLutSize, MAX_LUT_BYTES } from "@/lib/lut";
+
+// Stores a .cube LUT (a team's house grade, say) and returns the URL to pass
+// to the applyLut tool. Browsers rarely know a MIME type for .cube, so the
+// file is judged by its name and contents instead.
+export async function POST(request: Request) {
+  const session = await auth();
+  if (!session) {
+    return NextResponse.json({ error: "Unauthorized" }, { status: 401 });
+  }
+
+  if (!process.env.BLOB_READ_WRITE_TOKEN) {
+    console.error("BLOB_READ_WRITE_TOKEN is missing");
+    return NextResponse.json({ error: "Storage configuration missing" }, { status: 500 });
+  }
+
+  const formData = await request.formData();
+  const file = formData.get("file");
+
+  if (!(file instanceof File)) {
+    return NextResponse.json({ error: "No file uploaded" }, { status: 400 });
+  }
+  if (!file.name.toLowerCase().endsWith(".cube")) {
+    return NextResponse.json({ error: "File must be a .cube LUT" }, { status: 400 });
+  }
+  if (file.size > MAX_LUT_BYTES) {
+    return NextResponse.json({ error: `LUT must be smaller than ${MAX_LUT_BYTES / (1024 * 1024)}MB` }, { status: 400 });
+  }
+
+  const text = await file.text();
+  const size = cubeLutSize(text);
+  if (size === null) {
+    return NextResponse.json({ error: "File is not a valid 3D .cube LUT" }, { status: 400 });
+  }
+
+  try {
+    const safeFilename = `luts/${Date.now()}-${file.name.replace(/[^a-zA-Z0-9.-]/g, "_")}`;
+    const { url } = await put(safeFilename, text, {
+      access: "public",
+      token: process.env.BLOB_READ_WRITE_TOKEN,
+      contentType: "text/plain",
+      addRandomSuffix: false, // We already add a timestamp
+    });
+
+    return NextResponse.json({ url, name: file.name, size });
+  } catch (uploadError: any) {
+    console.error("LUT upload error:", uploadError);
+    return NextResponse.json({
+      error: "Failed to upload to Blob storage",
+      details: uploadError.message
+    }, { status: 500 });
+  }
+}
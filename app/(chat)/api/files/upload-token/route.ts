@@ -0,0 +1,56 @@
+import { handleUpload, type HandleUploadBody } from "@vercel/blob/client";
+import { NextResponse } from "next/server";
+import { auth } from "@/app/(auth)/auth";
+import { ALLOWED_UPLOAD_TYPES } from "@/lib/media-types";
+
+// Direct uploads skip the function body limit, so they can be much larger
+const MAX_DIRECT_UPLOAD_BYTES = 500 * 1024 * 1024;
+
+// The "<timestamp>-<sanitized name>" pathname multimodal-input builds, with
+// a timestamp from the last few minutes. Anything else could name an
+// existing blob, which the upload would overwrite.
+const UPLOAD_PATHNAME = /^(\d{13})-[a-zA-Z0-9._-]+$/;
+const MAX_PATHNAME_AGE_MS = 5 * 60 * 1000;
+
+function isFreshUploadPathname(pathname: string): boolean {
+  const match = pathname.match(UPLOAD_PATHNAME);
+  return !!match && Math.abs(Date.now() - Number(match[1])) < MAX_PATHNAME_AGE_MS;
+}
+
+// Issues short-lived client tokens so the browser uploads sources straight to
+// Vercel Blob instead of streaming them through this server. Blob also posts
+// here when an upload completes; that callback carries no session (it is
+// verified by handleUpload instead), so auth.config lets this path through.
+export async function POST(request: Request) {
+  const body = (await request.json()) as HandleUploadBody;
+
+  try {
+    const jsonResponse = await handleUpload({
+      body,
+      request,
+      onBeforeGenerateToken: async (pathname) => {
+        const session = await auth();
+        if (!session) {
+          throw new Error("Unauthorized");
+        }
+        if (!isFreshUploadPathname(pathname)) {
+          throw new Error("Invalid upload pathname");
+        }
+
+        return {
+          allowedContentTypes: ALLOWED_UPLOAD_TYPES,
+          maximumSizeInBytes: MAX_DIRECT_UPLOAD_BYTES,
+          addRandomSuffix: false, // The pathname checked above carries a timestamp
+        };
+      },
+      onUploadCompleted: async ({ blob }) => {
+        console.log("Direct upload completed:", blob.url);
+      },
+    });
+
+    return NextResponse.json(jsonResponse);
+  } catch (error: any) {
+    console.error("Upload token error:", error);
+    return NextResponse.json({ error: error.message }, { status: 400 });
+  }
+}
@@ -2,6 +2,7 @@ import { put } from "@vercel/blob";
 import { NextResponse } from "next/server";
 import { z } from "zod";
 import { auth } from "@/app/(auth)/auth";
+import { ALLOWED_UPLOAD_TYPES } from "@/lib/media-types";
 
 const FileSchema = z.object({
   file: z
@@ -11,27 +12,7 @@ const FileSchema = z.object({
     })
     .refine(
       (file) => {
-        const allowedTypes = [
-          // Videos
-          'video/mp4',
-          'video/webm',
-          'video/ogg',
-          'video/quicktime',
-          'video/x-msvideo',
-          'video/x-matroska',
-          // Images
-          'image/jpeg',
-          'image/png',
-          'image/gif',
-          'image/webp',
-          // Audio
-          'audio/mpeg',
-          'audio/wav',
-          'audio/ogg',
-          'audio/webm',
-          'audio/aac'
-        ];
-        return allowedTypes.includes(file.type);
+        return ALLOWED_UPLOAD_TYPES.includes(file.type);
       },
       {
         message: "File must be a video (MP4, WebM, etc.), image (JPEG, PNG, GIF, WebP), or audio (MP3, WAV, etc.)",
@@ -88,6 +69,7 @@ export async function POST(request: Request) {
       const { url } = await put(safeFilename, fileArrayBuffer, {
         access: 'public',
         token: process.env.BLOB_READ_WRITE_TOKEN,
+        contentType: file.type,
         addRandomSuffix: false // We already add timestamp
       });
 
@@ -0,0 +1,93 @@
+import fs from "fs";
+import path from "path";
+import { Readable } from "stream";
+
+import { auth } from "@/app/(auth)/auth";
+import { contentTypeFor } from "@/lib/media-types";
+import { localDownloadName, localOutputDirectory } from "@/lib/storage";
+
+// Serves outputs written by the local storage backend to signed-in users.
+// Players seek with Range requests, and the ETag lets browsers revalidate
+// cheaply. The middleware matcher doesn't reach this path, so the session is
+// checked here.
+export async function GET(
+  request: Request,
+  { params }: { params: { name: string } },
+) {
+  const session = await auth();
+  if (!session) {
+    return new Response("Unauthorized", { status: 401 });
+  }
+
+  const name = decodeURIComponent(params.name);
+  if (name !== path.basename(name)) {
+    return new Response("Not found", { status: 404 });
+  }
+
+  const filePath = path.join(localOutputDirectory(), name);
+  let stats: fs.Stats;
+  try {
+    stats = fs.statSync(filePath);
+  } catch {
+    return new Response("Not found", { status: 404 });
+  }
+  if (!stats.isFile()) {
+    return new Response("Not found", { status: 404 });
+  }
+
+  // Outputs are written once under a name with a random suffix (never
+  // replaced, see createLocalStorage), so size and write time identify the bytes
+  const etag = `"${stats.size.toString(16)}-${Math.floor(stats.mtimeMs).toString(16)}"`;
+  const downloadName = localDownloadName(name);
+  const configuredMaxAge = Number(process.env.VIDEO_OUTPUT_CACHE_MAX_AGE_SECONDS || 86400);
+  const maxAge = Number.isFinite(configuredMaxAge) ? configuredMaxAge : 86400;
+  const headers = new Headers({
+    "Accept-Ranges": "bytes",
+    // Only the browser may keep a copy; shared caches would skip the session check
+    "Cache-Control": `private, max-age=${maxAge}`,
+    "Content-Type": contentTypeFor(name),
+    ETag: etag,
+    "Last-Modified": stats.mtime.toUTCString(),
+    // Downloads drop the random suffix from the stored name; the ASCII
+    // fallback is for clients that ignore filename*
+    "Content-Disposition": `attachment; filename="${downloadName.replace(/[^\x20-\x7e]|["\\]/g, "_")}"; filename*=UTF-8''${encodeURIComponent(downloadName)}`,
+  });
+
+  if (request.headers.get("if-none-match") === etag) {
+    return new Response(null, { status: 304, headers });
+  }
+
+  let start = 0;
+  let end = stats.size - 1;
+  let status = 200;
+
+  // A single byte range; If-Range falls back to the whole file when stale
+  const range = request.headers.get("range")?.match(/^bytes=(\d*)-(\d*)$/);
+  const ifRange = request.headers.get("if-range");
+  if (range && (!ifRange || ifRange === etag) && (range[1] || range[2])) {
+    if (range[1]) {
+      start = Number(range[1]);
+      end = range[2] ? Math.min(Number(range[2]), stats.size - 1) : stats.size - 1;
+    } else {
+      // bytes=-N is the last N bytes
+      start = Math.max(stats.size - Number(range[2]), 0);
+    }
+
+    if (start > end || start >= stats.size) {
+      headers.set("Content-Range", `bytes */${stats.size}`);
+      return new Response(null, { status: 416, headers });
+    }
+    status = 206;
+    headers.set("Content-Range", `bytes ${start}-${end}/${stats.size}`);
+  }
+
+  headers.set("Content-Length", String(end - start + 1));
+
+  // Next answers HEAD with this handler too
+  if (request.method === "HEAD") {
+    return new Response(null, { status, headers });
+  }
+
+  const body = Readable.toWeb(fs.createReadStream(filePath, { start, end })) as ReadableStream;
+  return new Response(body, { status, headers });
+}
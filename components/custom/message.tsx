@@ -205,7 +205,16 @@ export const Message = ({
               const videoEditingTools = [
                 'trimVideo', 'adjustSpeed', 'adjustBrightness', 'addText', 
                 'cropVideo', 'rotateVideo', 'adjustVolume', 'applyFilter',
-                'getVideoFromAttachments'
+                'resizeMedia', 'convertImage', 'normalizeAudio', 'fadeAudio',
+                'transcodeAudio', 'renderWaveform', 'setChapters', 'replaceAudio',
+                'createSlideshow', 'reframe', 'addWatermark', 'addAnimatedOverlay',
+                'composeGrid', 'concatVideos', 'splitVideo', 'reverseVideo',
+                'createBoomerang', 'freezeFrame', 'slowMotion', 'changeFps',
+                'deinterlace', 'denoise', 'sharpen', 'stabilize',
+                'addVignette', 'addGrain', 'obscureRegion', 'chromaKey',
+                'applyLut', 'colorAdjust', 'whiteBalance',
+                'getVideoFromAttachments', 'getImageFromAttachments', 'getAudioFromAttachments',
+                'getAvailableOperations'
               ];
               
               // Find all video editing results
@@ -223,7 +232,7 @@ export const Message = ({
                 .map(inv => ({ ...inv, result: inv.result as any }))
                 .filter(inv => inv.result && typeof inv.result === 'object')
                 .reverse()
-                .find(inv => inv.result.success && (inv.result.videoUrl || inv.result.editedVideoUrl));
+                .find(inv => inv.result.success && (inv.result.videoUrl || inv.result.imageUrl || inv.result.audioUrl || inv.result.editedVideoUrl));
               
               const otherResults = toolInvocations
                 .filter(inv => inv.state === "result")
@@ -1,5 +1,6 @@
 "use client";
 
+import { upload } from "@vercel/blob/client";
 import { Attachment, ChatRequestOptions, CreateMessage, Message } from "ai";
 import { motion, AnimatePresence } from "framer-motion";
 import React, {
@@ -83,54 +84,49 @@ export function MultimodalInput({
       setIsUploading(true);
 
       const uploadFile = async (file: File) => {
-        const formData = new FormData();
-        formData.append("file", file);
-
         try {
-          const response = await fetch(`/api/files/upload`, {
-            method: "POST",
-            body: formData,
+          // Upload straight to Blob storage; the server only signs the upload
+          const timestamp = Date.now();
+          const safeFilename = `${timestamp}-${file.name.replace(/[^a-zA-Z0-9.-]/g, '_')}`;
+          const { url } = await upload(safeFilename, file, {
+            access: "public",
+            handleUploadUrl: "/api/files/upload-token",
+            contentType: file.type,
           });
+          const contentType = file.type;
 
-          if (response.ok) {
-            const data = await response.json();
-            const { url, name, contentType } = data;
-
-            const attachment = {
-              url,
-              name: name,
-              contentType: contentType,
-            };
+          const attachment = {
+            url,
+            name: file.name,
+            contentType: contentType,
+          };
 
-            console.log("Upload successful, attachment:", attachment);
+          console.log("Upload successful, attachment:", attachment);
 
-            // Set media type based on content type
-            let detectedMediaType: "video" | "image" | "audio" | null = null;
-            if (contentType.startsWith('video/')) {
-              detectedMediaType = 'video';
-            } else if (contentType.startsWith('image/')) {
-              detectedMediaType = 'image';
-            } else if (contentType.startsWith('audio/')) {
-              detectedMediaType = 'audio';
-            }
+          // Set media type based on content type
+          let detectedMediaType: "video" | "image" | "audio" | null = null;
+          if (contentType.startsWith('video/')) {
+            detectedMediaType = 'video';
+          } else if (contentType.startsWith('image/')) {
+            detectedMediaType = 'image';
+          } else if (contentType.startsWith('audio/')) {
+            detectedMediaType = 'audio';
+          }
 
-            setMediaType(detectedMediaType);
-            setMediaFile(attachment);
-            setAttachments([attachment]);
-            
-            console.log("State after upload:");
-            console.log("- detectedMediaType:", detectedMediaType);
-            console.log("- attachment being set as mediaFile:", attachment);
-            console.log("- attachments array:", [attachment]);
-            
-            toast.success(`${detectedMediaType || 'File'} uploaded successfully!`);
+          setMediaType(detectedMediaType);
+          setMediaFile(attachment);
+          setAttachments([attachment]);
+          
+          console.log("State after upload:");
+          console.log("- detectedMediaType:", detectedMediaType);
+          console.log("- attachment being set as mediaFile:", attachment);
+          console.log("- attachments array:", [attachment]);
+          
+          toast.success(`${detectedMediaType || 'File'} uploaded successfully!`);
 
-            return attachment;
-          } else {
-            const { error } = await response.json();
-            toast.error(error);
-          }
+          return attachment;
         } catch (error) {
+          console.error("Upload failed:", error);
           toast.error("Failed to upload file, please try again!");
         } finally {
           setUploadQueue([]);
@@ -8,7 +8,11 @@ interface VideoResultProps {
   result: {
     success?: boolean;
     videoUrl?: string;
+    imageUrl?: string;
+    audioUrl?: string;
     editedVideoUrl?: string;
+    segmentUrls?: string[];
+    originalFilename?: string;
     message?: string;
     error?: string;
     duration?: string;
@@ -30,7 +34,7 @@ export function VideoResult({ result }: VideoResultProps) {
     );
   }
 
-  const videoUrl = result.editedVideoUrl || result.videoUrl;
+  const videoUrl = result.editedVideoUrl || result.videoUrl || result.imageUrl || result.audioUrl;
   
   if (!videoUrl) {
     return (
@@ -48,7 +52,10 @@ export function VideoResult({ result }: VideoResultProps) {
       const url = window.URL.createObjectURL(blob);
       const a = document.createElement('a');
       a.href = url;
-      a.download = `edited-video-${Date.now()}.mp4`;
+      const extension = new URL(videoUrl).pathname.split('.').pop() || 'mp4';
+      a.download = result.originalFilename
+        ? `edited-${result.originalFilename.replace(/\.[^.]*$/, '')}.${extension}`
+        : `edited-video-${Date.now()}.${extension}`;
       document.body.appendChild(a);
       a.click();
       window.URL.revokeObjectURL(url);
@@ -64,18 +71,38 @@ export function VideoResult({ result }: VideoResultProps) {
     window.open(videoUrl, '_blank');
   };
 
+  const isImage = /\.(jpe?g|png|webp|gif)$/i.test(new URL(videoUrl).pathname);
+  const isAudio = /\.(mp3|wav|m4a|aac)$/i.test(new URL(videoUrl).pathname);
+  // Bundled outputs can only be downloaded, not previewed
+  const isArchive = /\.zip$/i.test(new URL(videoUrl).pathname);
+
   return (
     <div className="space-y-3">
       {/* Video Preview */}
-      <div className="rounded-lg overflow-hidden bg-muted/50">
-        <video
-          src={videoUrl}
-          controls
-          className="w-full h-auto"
-          style={{ maxHeight: '400px' }}
-          preload="metadata"
-        />
-      </div>
+      {!isArchive && (
+        <div className="rounded-lg overflow-hidden bg-muted/50">
+          {isImage ? (
+            // NOTE: it is recommended to use next/image for images
+            // eslint-disable-next-line @next/next/no-img-element
+            <img
+              src={videoUrl}
+              alt={result.originalFilename || "Edited image"}
+              className="w-full h-auto object-contain"
+              style={{ maxHeight: '400px' }}
+            />
+          ) : isAudio ? (
+            <audio src={videoUrl} controls className="w-full" preload="metadata" />
+          ) : (
+            <video
+              src={videoUrl}
+              controls
+              className="w-full h-auto"
+              style={{ maxHeight: '400px' }}
+              preload="metadata"
+            />
+          )}
+        </div>
+      )}
 
       {/* Simple Actions */}
       <div className="flex items-center gap-2">
@@ -101,6 +128,24 @@ export function VideoResult({ result }: VideoResultProps) {
         </Button>
       </div>
 
+      {/* Every part of a split, when there are several */}
+      {result.segmentUrls && result.segmentUrls.length > 1 && (
+        <ol className="list-decimal pl-5 text-sm space-y-1">
+          {result.segmentUrls.map((segmentUrl, index) => (
+            <li key={segmentUrl}>
+              <a
+                href={segmentUrl}
+                target="_blank"
+                rel="noreferrer"
+                className="text-zinc-600 dark:text-zinc-400 underline"
+              >
+                Part {index + 1}
+              </a>
+            </li>
+          ))}
+        </ol>
+      )}
+
       {/* Optional message */}
       {result.message && (
         <p className="text-sm text-zinc-600 dark:text-zinc-400">
@@ -0,0 +1,15 @@
+// Runs once when the server starts. Probing ffmpeg here means edits the
+// installed build can't run are logged at boot, not on the first request.
+export async function register() {
+  if (process.env.NEXT_RUNTIME !== "nodejs") {
+    return;
+  }
+
+  const { detectCapabilities } = await import("@/lib/video-processor");
+  try {
+    await detectCapabilities();
+  } catch (error) {
+    // Not fatal: detection is retried when the first edit needs it
+    console.error("Failed to detect FFmpeg capabilities at startup:", error);
+  }
+}
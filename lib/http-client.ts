@@ -0,0 +1,99 @@
+// Fetch wrapper used for pulling remote sources. Connections are pooled and
+// kept alive by Node's global fetch dispatcher; this adds the timeouts and
+// redirect limits plain fetch lacks. Retries are left to the caller's stage
+// policy (see VideoProcessor.withRetry) since only it knows what is safe to repeat.
+
+import type { UrlGuard } from "./url-guard";
+
+export type HttpClient = (url: string, init?: RequestInit) => Promise<Response>;
+
+export interface HttpClientOptions {
+  // Time allowed for the connection and response headers
+  headersTimeoutMs?: number;
+  // Longest gap allowed between body chunks once the response has started
+  idleTimeoutMs?: number;
+  maxRedirects?: number;
+  // Checked before the first request and before following each redirect
+  urlGuard?: UrlGuard;
+}
+
+export function createHttpClient(options: HttpClientOptions = {}): HttpClient {
+  const {
+    headersTimeoutMs = 30_000,
+    idleTimeoutMs = 60_000,
+    maxRedirects = 5,
+    urlGuard,
+  } = options;
+
+  return async (url, init = {}) => {
+    const controller = new AbortController();
+    let timer = setTimeout(
+      () => controller.abort(new Error(`No response from ${url} within ${headersTimeoutMs}ms`)),
+      headersTimeoutMs,
+    );
+
+    let response: Response;
+    try {
+      response = await followRedirects(url, { ...init, signal: controller.signal }, maxRedirects, urlGuard);
+    } finally {
+      clearTimeout(timer);
+    }
+
+    if (!response.body) {
+      return response;
+    }
+
+    // Re-arm the idle timer on every chunk so slow-but-steady transfers of
+    // large files are fine while a stalled connection still fails
+    const armIdleTimer = () => {
+      clearTimeout(timer);
+      timer = setTimeout(
+        () => controller.abort(new Error(`Download from ${url} stalled for ${idleTimeoutMs}ms`)),
+        idleTimeoutMs,
+      );
+    };
+    const body = response.body.pipeThrough(
+      new TransformStream<Uint8Array, Uint8Array>({
+        start: armIdleTimer,
+        transform(chunk, stream) {
+          armIdleTimer();
+          stream.enqueue(chunk);
+        },
+        flush() {
+          clearTimeout(timer);
+        },
+      }),
+    );
+
+    return new Response(body, {
+      status: response.status,
+      statusText: response.statusText,
+      headers: response.headers,
+    });
+  };
+}
+
+async function followRedirects(
+  url: string,
+  init: RequestInit,
+  maxRedirects: number,
+  urlGuard?: UrlGuard,
+): Promise<Response> {
+  let current = url;
+
+  for (let hop = 0; ; hop++) {
+    await urlGuard?.(new URL(current));
+    const response = await fetch(current, { ...init, redirect: "manual" });
+    const location = response.headers.get("location");
+
+    if (response.status < 300 || response.status >= 400 || !location) {
+      return response;
+    }
+    if (hop >= maxRedirects) {
+      throw new Error(`Too many redirects fetching ${url}`);
+    }
+
+    await response.body?.cancel();
+    current = new URL(location, current).toString();
+  }
+}
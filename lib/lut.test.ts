@@ -0,0 +1,42 @@
+import assert from 'node:assert/strict';
+import { test } from 'node:test';
+
+import { cubeLutSize } from './lut';
+
+// An identity cube with `points` entries per axis, red varying fastest
+function identityCube(points: number, header = `LUT_3D_SIZE ${points}`): string {
+  const rows: string[] = [];
+  for (let b = 0; b < points; b++) {
+    for (let g = 0; g < points; g++) {
+      for (let r = 0; r < points; r++) {
+        rows.push([r, g, b].map((value) => (value / (points - 1)).toFixed(6)).join(' '));
+      }
+    }
+  }
+  return ['TITLE "identity"', header, ...rows].join('\n');
+}
+
+test('accepts a 3D cube and returns its size', () => {
+  assert.equal(cubeLutSize(identityCube(2)), 2);
+  assert.equal(cubeLutSize(identityCube(17)), 17);
+});
+
+test('accepts rows in exponent notation', () => {
+  const text = identityCube(2).replace('0.000000 0.000000 0.000000', '0e-5 1.5e-3 -2e-4');
+  assert.equal(cubeLutSize(text), 2);
+});
+
+test('rejects 1D LUTs and text without a size', () => {
+  assert.equal(cubeLutSize(identityCube(2, 'LUT_1D_SIZE 2')), null);
+  assert.equal(cubeLutSize('not a lut'), null);
+});
+
+test('rejects sizes outside 2-65', () => {
+  assert.equal(cubeLutSize('LUT_3D_SIZE 1\n0 0 0'), null);
+  assert.equal(cubeLutSize('LUT_3D_SIZE 66'), null);
+});
+
+test('rejects a table with the wrong number of rows', () => {
+  const text = identityCube(2);
+  assert.equal(cubeLutSize(text.slice(0, text.lastIndexOf('\n'))), null);
+});
@@ -0,0 +1,27 @@
+// 3D LUTs in the Adobe/Resolve .cube text format, as exported by most
+// grading tools. Only the header is checked here; ffmpeg's lut3d filter does
+// the actual parsing when the grade is applied.
+
+// A 65-point cube is about 7MB of text; nothing sensible is larger
+export const MAX_LUT_BYTES = 16 * 1024 * 1024;
+
+// lut3d accepts sizes from 2 to 256, but anything past 65 is unheard of
+const MAX_LUT_SIZE = 65;
+
+// Returns the cube size (points per axis), or null when the text isn't a
+// 3D .cube LUT. 1D LUTs (LUT_1D_SIZE) are rejected since lut3d can't use them.
+export function cubeLutSize(text: string): number | null {
+  const size = text.match(/^\s*LUT_3D_SIZE\s+(\d+)\s*$/m);
+  if (!size) {
+    return null;
+  }
+
+  const points = Number(size[1]);
+  if (points < 2 || points > MAX_LUT_SIZE) {
+    return null;
+  }
+
+  // The table itself: one "r g b" line per entry
+  const rows = text.match(/^\s*-?[\d.]+(?:e-?\d+)?\s+-?[\d.]+(?:e-?\d+)?\s+-?[\d.]+(?:e-?\d+)?\s*$/gim);
+  return rows && rows.length === points ** 3 ? points : null;
+}
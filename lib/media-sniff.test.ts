@@ -0,0 +1,55 @@
+import assert from 'node:assert/strict';
+import { test } from 'node:test';
+
+import { sniffMedia } from './media-sniff';
+
+// Leading bytes padded out to what sniffMediaFile would read
+function header(...parts: Array<string | number[]>): Buffer {
+  const bytes = Buffer.concat(parts.map((part) => (typeof part === 'string' ? Buffer.from(part, 'latin1') : Buffer.from(part))));
+  return Buffer.concat([bytes, Buffer.alloc(Math.max(0, 64 - bytes.length))]);
+}
+
+test('identifies ISO base media by brand', () => {
+  assert.deepEqual(sniffMedia(header([0, 0, 0, 0x20], 'ftypisom')), { extension: 'mp4', kind: 'video' });
+  assert.deepEqual(sniffMedia(header([0, 0, 0, 0x14], 'ftypqt  ')), { extension: 'mov', kind: 'video' });
+  assert.deepEqual(sniffMedia(header([0, 0, 0, 0x20], 'ftypM4A ')), { extension: 'm4a', kind: 'audio' });
+  assert.deepEqual(sniffMedia(header([0, 0, 0, 0x08], 'moov')), { extension: 'mov', kind: 'video' });
+});
+
+test('tells WebM from other Matroska files', () => {
+  assert.deepEqual(sniffMedia(header([0x1a, 0x45, 0xdf, 0xa3, 0x9f, 0x42, 0x82, 0x84], 'webm')), { extension: 'webm', kind: 'video' });
+  assert.deepEqual(sniffMedia(header([0x1a, 0x45, 0xdf, 0xa3, 0x9f, 0x42, 0x82, 0x88], 'matroska')), { extension: 'mkv', kind: 'video' });
+});
+
+test('identifies RIFF formats and rejects unknown ones', () => {
+  assert.deepEqual(sniffMedia(header('RIFF', [0, 0, 0, 0], 'WAVE')), { extension: 'wav', kind: 'audio' });
+  assert.deepEqual(sniffMedia(header('RIFF', [0, 0, 0, 0], 'AVI ')), { extension: 'avi', kind: 'video' });
+  assert.deepEqual(sniffMedia(header('RIFF', [0, 0, 0, 0], 'WEBP')), { extension: 'webp', kind: 'image' });
+  assert.equal(sniffMedia(header('RIFF', [0, 0, 0, 0], 'ABCD')), null);
+});
+
+test('identifies images', () => {
+  assert.deepEqual(sniffMedia(header([0x89], 'PNG\r\n')), { extension: 'png', kind: 'image' });
+  assert.deepEqual(sniffMedia(header([0xff, 0xd8, 0xff, 0xe0])), { extension: 'jpg', kind: 'image' });
+  assert.deepEqual(sniffMedia(header('GIF89a')), { extension: 'gif', kind: 'image' });
+});
+
+test('tells ADTS AAC from MPEG audio frames', () => {
+  assert.deepEqual(sniffMedia(header([0xff, 0xf1, 0x50, 0x80])), { extension: 'aac', kind: 'audio' });
+  assert.deepEqual(sniffMedia(header([0xff, 0xfb, 0x90, 0x64])), { extension: 'mp3', kind: 'audio' });
+  assert.deepEqual(sniffMedia(header('ID3', [4, 0])), { extension: 'mp3', kind: 'audio' });
+});
+
+test('identifies MPEG-TS only with a second sync byte one packet in', () => {
+  const packets = Buffer.alloc(189);
+  packets[0] = 0x47;
+  packets[188] = 0x47;
+  assert.deepEqual(sniffMedia(packets), { extension: 'ts', kind: 'video' });
+  assert.equal(sniffMedia(header([0x47])), null);
+});
+
+test('rejects short headers and non-media files', () => {
+  assert.equal(sniffMedia(Buffer.from('short')), null);
+  assert.equal(sniffMedia(header('<!DOCTYPE html>')), null);
+  assert.equal(sniffMedia(header('%PDF-1.7')), null);
+});
@@ -0,0 +1,82 @@
+import fs from 'fs';
+
+export type MediaKind = 'video' | 'image' | 'audio';
+
+export interface SniffedMedia {
+  extension: string;
+  kind: MediaKind;
+}
+
+function ascii(header: Buffer, start: number, end: number): string {
+  return header.subarray(start, end).toString('latin1');
+}
+
+// Identifies the container from its leading bytes so inputs keep an extension
+// that matches their contents (ffmpeg picks demuxers partly by extension) and
+// anything that isn't media is turned away before ffmpeg ever sees it.
+export function sniffMedia(header: Buffer): SniffedMedia | null {
+  if (header.length < 12) {
+    return null;
+  }
+
+  // ISO base media: size, then an ftyp (or a bare moov/mdat in old QuickTime files)
+  const box = ascii(header, 4, 8);
+  if (box === 'ftyp') {
+    const brand = ascii(header, 8, 12);
+    if (brand === 'qt  ') return { extension: 'mov', kind: 'video' };
+    if (brand === 'M4A ' || brand === 'M4B ') return { extension: 'm4a', kind: 'audio' };
+    return { extension: 'mp4', kind: 'video' };
+  }
+  if (['moov', 'mdat', 'wide', 'free'].includes(box)) {
+    return { extension: 'mov', kind: 'video' };
+  }
+
+  // EBML header; WebM declares its doctype within the first few dozen bytes
+  if (header[0] === 0x1a && header[1] === 0x45 && header[2] === 0xdf && header[3] === 0xa3) {
+    return ascii(header, 0, 64).includes('webm')
+      ? { extension: 'webm', kind: 'video' }
+      : { extension: 'mkv', kind: 'video' };
+  }
+
+  if (ascii(header, 0, 4) === 'RIFF') {
+    const format = ascii(header, 8, 12);
+    if (format === 'AVI ') return { extension: 'avi', kind: 'video' };
+    if (format === 'WAVE') return { extension: 'wav', kind: 'audio' };
+    if (format === 'WEBP') return { extension: 'webp', kind: 'image' };
+    return null;
+  }
+
+  // MPEG-TS packets are 188 bytes, each starting with a 0x47 sync byte
+  if (header[0] === 0x47 && header.length > 188 && header[188] === 0x47) {
+    return { extension: 'ts', kind: 'video' };
+  }
+
+  if (ascii(header, 0, 3) === 'FLV') return { extension: 'flv', kind: 'video' };
+  if (header[0] === 0x30 && header[1] === 0x26 && header[2] === 0xb2 && header[3] === 0x75) {
+    return { extension: 'wmv', kind: 'video' };
+  }
+  if (ascii(header, 0, 4) === 'OggS') return { extension: 'ogg', kind: 'audio' };
+  if (ascii(header, 0, 4) === 'fLaC') return { extension: 'flac', kind: 'audio' };
+  if (ascii(header, 0, 3) === 'ID3') return { extension: 'mp3', kind: 'audio' };
+
+  if (header[0] === 0x89 && ascii(header, 1, 4) === 'PNG') return { extension: 'png', kind: 'image' };
+  if (header[0] === 0xff && header[1] === 0xd8 && header[2] === 0xff) return { extension: 'jpg', kind: 'image' };
+  if (ascii(header, 0, 4) === 'GIF8') return { extension: 'gif', kind: 'image' };
+
+  // Bare audio frames: ADTS AAC (0xFFF1/0xFFF9) before generic MPEG audio sync
+  if (header[0] === 0xff && (header[1] & 0xf6) === 0xf0) return { extension: 'aac', kind: 'audio' };
+  if (header[0] === 0xff && (header[1] & 0xe0) === 0xe0) return { extension: 'mp3', kind: 'audio' };
+
+  return null;
+}
+
+export function sniffMediaFile(filePath: string): SniffedMedia | null {
+  const header = Buffer.alloc(512);
+  const fd = fs.openSync(filePath, 'r');
+  try {
+    const bytesRead = fs.readSync(fd, header, 0, header.length, 0);
+    return sniffMedia(header.subarray(0, bytesRead));
+  } finally {
+    fs.closeSync(fd);
+  }
+}
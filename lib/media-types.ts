@@ -0,0 +1,49 @@
+import path from 'path';
+
+// Media the chat accepts as attachments, shared by the upload routes
+export const ALLOWED_UPLOAD_TYPES = [
+  // Videos
+  'video/mp4',
+  'video/webm',
+  'video/ogg',
+  'video/quicktime',
+  'video/x-msvideo',
+  'video/x-matroska',
+  // Images
+  'image/jpeg',
+  'image/png',
+  'image/gif',
+  'image/webp',
+  // Audio
+  'audio/mpeg',
+  'audio/wav',
+  'audio/ogg',
+  'audio/webm',
+  'audio/aac'
+];
+
+// Served with stored outputs, so players and browsers see what the file really is
+const OUTPUT_CONTENT_TYPES: Record<string, string> = {
+  mp4: 'video/mp4',
+  mov: 'video/quicktime',
+  mkv: 'video/x-matroska',
+  webm: 'video/webm',
+  gif: 'image/gif',
+  png: 'image/png',
+  jpg: 'image/jpeg',
+  jpeg: 'image/jpeg',
+  webp: 'image/webp',
+  mp3: 'audio/mpeg',
+  m4a: 'audio/mp4',
+  aac: 'audio/aac',
+  wav: 'audio/wav',
+  ogg: 'audio/ogg',
+  flac: 'audio/flac',
+  m3u8: 'application/x-mpegURL',
+  zip: 'application/zip',
+};
+
+export function contentTypeFor(filePath: string): string {
+  const extension = path.extname(filePath).slice(1).toLowerCase();
+  return OUTPUT_CONTENT_TYPES[extension] ?? 'application/octet-stream';
+}
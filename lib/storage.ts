@@ -0,0 +1,149 @@
+// Where processed outputs end up. VideoProcessor only picks the name and
+// hands over the finished file; each backend decides how it is stored and
+// what public URL comes back.
+
+import { randomBytes } from 'crypto';
+import fs from 'fs';
+import path from 'path';
+import { put } from '@vercel/blob';
+
+export interface StoreOptions {
+  contentType: string;
+}
+
+export interface StorageBackend {
+  // Stores the file and returns the URL it can be fetched from. `name` is
+  // what the file downloads as; the stored key always gets a random suffix,
+  // so outputs can't be guessed from their source name or overwrite each other.
+  store(filePath: string, name: string, options: StoreOptions): Promise<string>;
+  // Maps a URL this backend handed out back to the stored file, so chained
+  // edits read it directly instead of fetching it over HTTP
+  localPathFor?(url: string): string | null;
+}
+
+// Raised when an output can't fit in the store at all; retrying won't help
+export class StorageQuotaError extends Error {
+  constructor(message: string) {
+    super(message);
+    this.name = 'StorageQuotaError';
+  }
+}
+
+export interface VercelBlobStorageOptions {
+  token?: string;
+  // How long browsers and the CDN may cache uploaded outputs
+  cacheMaxAgeSeconds?: number;
+}
+
+// Vercel Blob requires parts of at least 5MB; below this one request is fine
+const MULTIPART_UPLOAD_BYTES = 100 * 1024 * 1024;
+
+export function createVercelBlobStorage(options: VercelBlobStorageOptions = {}): StorageBackend {
+  return {
+    async store(filePath, name, { contentType }) {
+      // Streamed from disk; large outputs go up in parts so a dropped
+      // connection doesn't restart the whole transfer. Blob serves the file
+      // with a Content-Disposition naming it without the suffix.
+      const blob = await put(name, fs.createReadStream(filePath), {
+        access: 'public',
+        contentType,
+        addRandomSuffix: true,
+        multipart: fs.statSync(filePath).size > MULTIPART_UPLOAD_BYTES,
+        cacheControlMaxAge: options.cacheMaxAgeSeconds,
+        token: options.token,
+      });
+      return blob.url;
+    },
+  };
+}
+
+// Served by app/processed/[name]/route.ts, so it must not be under public/
+export function localOutputDirectory(): string {
+  return process.env.VIDEO_LOCAL_OUTPUT_DIR || path.join(process.cwd(), 'processed');
+}
+
+// Local outputs are stored as <name>-<32 hex digits>.<ext>; this recovers
+// the name they download as
+export function localDownloadName(storedName: string): string {
+  return storedName.replace(/-[0-9a-f]{32}(\.[^.]*)?$/, '$1');
+}
+
+export interface LocalStorageOptions {
+  // Directory outputs are copied into
+  directory: string;
+  // Public origin of the server, e.g. https://media.example.com
+  baseUrl: string;
+  // Path the directory is served under on that origin, e.g. /processed
+  urlPrefix: string;
+  // Total size the directory may grow to; least recently used outputs are
+  // evicted to make room. 0 or unset means unlimited.
+  quotaBytes?: number;
+}
+
+// Copies outputs into a directory served by the app at /processed (or by a
+// reverse proxy pointed at the same directory).
+export function createLocalStorage(options: LocalStorageOptions): StorageBackend {
+  const publicUrl = `${options.baseUrl.replace(/\/+$/, '')}/${options.urlPrefix.replace(/^\/+|\/+$/g, '')}`;
+  const quotaBytes = options.quotaBytes ?? 0;
+
+  // Evicts least recently used outputs (by access time, falling back to
+  // write time) until `incomingBytes` more fit under the quota
+  const makeRoom = (incomingBytes: number) => {
+    if (quotaBytes <= 0) {
+      return;
+    }
+    if (incomingBytes > quotaBytes) {
+      throw new StorageQuotaError(`Output is larger than the ${Math.round(quotaBytes / (1024 * 1024))}MB storage quota`);
+    }
+
+    const files = fs.readdirSync(options.directory)
+      .map((name) => {
+        const filePath = path.join(options.directory, name);
+        const stats = fs.statSync(filePath);
+        return { filePath, size: stats.size, usedAt: Math.max(stats.atimeMs, stats.mtimeMs), isFile: stats.isFile() };
+      })
+      .filter((file) => file.isFile)
+      .sort((a, b) => a.usedAt - b.usedAt);
+
+    let totalBytes = files.reduce((sum, file) => sum + file.size, 0);
+    for (const file of files) {
+      if (totalBytes + incomingBytes <= quotaBytes) {
+        break;
+      }
+      fs.rmSync(file.filePath, { force: true });
+      totalBytes -= file.size;
+    }
+  };
+
+  return {
+    async store(filePath, name) {
+      const extension = path.extname(name);
+      const fileName = `${path.basename(name, extension)}-${randomBytes(16).toString('hex')}${extension}`;
+
+      fs.mkdirSync(options.directory, { recursive: true });
+      makeRoom(fs.statSync(filePath).size);
+      // Never replaces an existing file, so a served name always means the same bytes
+      await fs.promises.copyFile(filePath, path.join(options.directory, fileName), fs.constants.COPYFILE_EXCL);
+      return `${publicUrl}/${encodeURIComponent(fileName)}`;
+    },
+
+    localPathFor(url) {
+      if (!url.startsWith(`${publicUrl}/`)) {
+        return null;
+      }
+      const fileName = decodeURIComponent(url.slice(publicUrl.length + 1).split(/[?#]/)[0]);
+      // Only plain names this backend could have written
+      if (fileName !== path.basename(fileName)) {
+        return null;
+      }
+      const filePath = path.join(options.directory, fileName);
+      if (!fs.existsSync(filePath)) {
+        return null;
+      }
+      // Record the use ourselves; many filesystems are mounted noatime
+      const now = new Date();
+      fs.utimesSync(filePath, now, fs.statSync(filePath).mtime);
+      return filePath;
+    },
+  };
+}
@@ -0,0 +1,47 @@
+import assert from "node:assert/strict";
+import { test } from "node:test";
+
+import { BlockedUrlError, createUrlGuard } from "./url-guard";
+
+// Literal addresses are judged without a DNS lookup, so these run offline
+test("refuses schemes other than http and https", async () => {
+  const guard = createUrlGuard();
+  await assert.rejects(guard(new URL("file:///etc/passwd")), BlockedUrlError);
+  await assert.rejects(guard(new URL("ftp://93.184.216.34/video.mp4")), BlockedUrlError);
+});
+
+test("refuses private, loopback and link-local addresses", async () => {
+  const guard = createUrlGuard();
+  for (const url of [
+    "http://127.0.0.1/video.mp4",
+    "http://10.1.2.3/video.mp4",
+    "http://172.16.0.1/video.mp4",
+    "http://192.168.1.1/video.mp4",
+    "http://169.254.169.254/latest/meta-data",
+    "http://[::1]/video.mp4",
+    "http://[fd00::1]/video.mp4",
+    "http://[::ffff:10.0.0.1]/video.mp4",
+  ]) {
+    await assert.rejects(guard(new URL(url)), BlockedUrlError, url);
+  }
+});
+
+test("allows public addresses", async () => {
+  const guard = createUrlGuard();
+  await guard(new URL("https://93.184.216.34/video.mp4"));
+  await guard(new URL("https://[2606:2800:220:1:248:1893:25c8:1946]/video.mp4"));
+});
+
+test("limits fetches to the allowed hosts and their subdomains", async () => {
+  const guard = createUrlGuard({ allowedHosts: ["Example.com"], allowPrivateAddresses: true });
+  await guard(new URL("https://example.com/video.mp4"));
+  await guard(new URL("https://cdn.example.com/video.mp4"));
+  await assert.rejects(guard(new URL("https://example.com.evil.test/video.mp4")), BlockedUrlError);
+  await assert.rejects(guard(new URL("https://notexample.com/video.mp4")), BlockedUrlError);
+});
+
+test("skips the address check when private addresses are allowed", async () => {
+  const guard = createUrlGuard({ allowPrivateAddresses: true });
+  await guard(new URL("http://127.0.0.1:3000/video.mp4"));
+  await assert.rejects(guard(new URL("file:///etc/passwd")), BlockedUrlError);
+});
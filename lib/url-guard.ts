@@ -0,0 +1,98 @@
+// Keeps source URLs pointed at the public internet. Without this a chat
+// message could have the server fetch cloud metadata endpoints
+// (169.254.169.254) or services on the private network and hand back the
+// response as a "video". The check runs on the first URL and again on every
+// redirect hop (see createHttpClient).
+
+import { lookup } from "dns/promises";
+import { BlockList, isIP } from "net";
+
+export type UrlGuard = (url: URL) => Promise<void>;
+
+export interface UrlGuardOptions {
+  // When non-empty, only these hosts (and their subdomains) may be fetched
+  allowedHosts?: string[];
+  // Skips the address check, for development against local servers
+  allowPrivateAddresses?: boolean;
+}
+
+export class BlockedUrlError extends Error {
+  constructor(message: string) {
+    super(message);
+    this.name = "BlockedUrlError";
+  }
+}
+
+const ALLOWED_PROTOCOLS = ["http:", "https:"];
+
+const PRIVATE_RANGES = new BlockList();
+for (const [network, prefix] of [
+  ["0.0.0.0", 8],
+  ["10.0.0.0", 8],
+  ["100.64.0.0", 10],
+  ["127.0.0.0", 8],
+  ["169.254.0.0", 16],
+  ["172.16.0.0", 12],
+  ["192.0.0.0", 24],
+  ["192.168.0.0", 16],
+  ["198.18.0.0", 15],
+  ["224.0.0.0", 4],
+  ["240.0.0.0", 4],
+] as const) {
+  PRIVATE_RANGES.addSubnet(network, prefix, "ipv4");
+}
+for (const [network, prefix] of [
+  ["::", 128],
+  ["::1", 128],
+  ["fc00::", 7],
+  ["fe80::", 10],
+  ["ff00::", 8],
+] as const) {
+  PRIVATE_RANGES.addSubnet(network, prefix, "ipv6");
+}
+
+function isPrivateAddress(address: string): boolean {
+  // IPv4-mapped IPv6 (::ffff:10.0.0.1) is judged by the IPv4 address inside
+  const mapped = address.match(/^::ffff:(\d+\.\d+\.\d+\.\d+)$/i);
+  if (mapped) {
+    return PRIVATE_RANGES.check(mapped[1], "ipv4");
+  }
+  return PRIVATE_RANGES.check(address, isIP(address) === 6 ? "ipv6" : "ipv4");
+}
+
+export function createUrlGuard(options: UrlGuardOptions = {}): UrlGuard {
+  const allowedHosts = (options.allowedHosts ?? []).map((host) => host.toLowerCase());
+
+  return async (url) => {
+    if (!ALLOWED_PROTOCOLS.includes(url.protocol)) {
+      throw new BlockedUrlError(`Unsupported URL scheme ${url.protocol}`);
+    }
+
+    const host = url.hostname.toLowerCase().replace(/^\[|\]$/g, "");
+    if (
+      allowedHosts.length > 0 &&
+      !allowedHosts.some((allowed) => host === allowed || host.endsWith(`.${allowed}`))
+    ) {
+      throw new BlockedUrlError(`Host ${host} is not in the allowed hosts list`);
+    }
+
+    if (options.allowPrivateAddresses) {
+      return;
+    }
+
+    // Every address the name resolves to must be public, not just the first
+    let addresses: string[];
+    try {
+      addresses = isIP(host)
+        ? [host]
+        : (await lookup(host, { all: true, verbatim: true })).map((entry) => entry.address);
+    } catch (error: any) {
+      throw new BlockedUrlError(`Could not resolve ${host}: ${error.message}`);
+    }
+
+    const blocked = addresses.find(isPrivateAddress);
+    if (blocked) {
+      throw new BlockedUrlError(`Host ${host} resolves to non-public address ${blocked}`);
+    }
+  };
+}
@@ -0,0 +1,68 @@
+import assert from 'node:assert/strict';
+import { test } from 'node:test';
+
+import {
+  atempoChain,
+  concatListEntry,
+  escapeFilterPath,
+  expandOutputName,
+  originalFilenameFromUrl,
+  parseTimeInput,
+  ProcessingError,
+  sanitizeStem,
+} from './video-processor';
+
+test('atempoChain keeps each factor within 0.5-2.0', () => {
+  assert.equal(atempoChain(1.5), 'atempo=1.5');
+  assert.equal(atempoChain(3), 'atempo=2,atempo=1.5');
+  assert.equal(atempoChain(4), 'atempo=2,atempo=2');
+  assert.equal(atempoChain(0.25), 'atempo=0.5,atempo=0.5');
+});
+
+test('parseTimeInput reads seconds, timecodes and frame numbers', () => {
+  assert.equal(parseTimeInput(12.5), 12.5);
+  assert.equal(parseTimeInput(' 12.5 '), 12.5);
+  assert.equal(parseTimeInput('01:30'), 90);
+  assert.equal(parseTimeInput('1:02:03.5'), 3723.5);
+  assert.equal(parseTimeInput('250f', 25), 10);
+  assert.equal(parseTimeInput('250F', 50), 5);
+});
+
+test('parseTimeInput refuses frames without a frame rate and malformed times', () => {
+  assert.throws(() => parseTimeInput('250f'), ProcessingError);
+  assert.throws(() => parseTimeInput('1:2:3:4'), ProcessingError);
+  assert.throws(() => parseTimeInput('ten'), ProcessingError);
+});
+
+test('sanitizeStem keeps a readable, extension-less stem', () => {
+  assert.equal(sanitizeStem('My Clip (final).mov'), 'My-Clip-final');
+  assert.equal(sanitizeStem('../../etc/passwd'), 'passwd');
+  assert.equal(sanitizeStem('vacation_2024-v2.mp4'), 'vacation_2024-v2');
+  assert.equal(sanitizeStem('???.mp4'), '');
+});
+
+test('expandOutputName fills known placeholders and leaves others', () => {
+  const values = { originalFilename: 'Holiday video.mp4', operation: 'trimVideo', extension: 'mp4' };
+  assert.equal(expandOutputName('{input_stem}_{operation}.{ext}', values), 'Holiday-video_trimVideo.mp4');
+  assert.equal(expandOutputName('{unknown}-intro', values), '{unknown}-intro');
+  assert.match(expandOutputName('{date}', values), /^\d{4}-\d{2}-\d{2}$/);
+  assert.match(expandOutputName('{timestamp}', values), /^\d{13}$/);
+});
+
+test('originalFilenameFromUrl drops the upload timestamp', () => {
+  assert.equal(originalFilenameFromUrl('https://store.example.com/1700000000000-clip.mp4'), 'clip.mp4');
+  assert.equal(originalFilenameFromUrl('https://store.example.com/videos/My%20Clip.mov'), 'My Clip.mov');
+  assert.equal(originalFilenameFromUrl('not a url'), 'video.mp4');
+});
+
+test('concatListEntry quotes paths containing quotes', () => {
+  assert.equal(concatListEntry('/tmp/part_001.mp4'), "file '/tmp/part_001.mp4'");
+  assert.equal(concatListEntry("/tmp/it's.mp4"), "file '/tmp/it'\\''s.mp4'");
+});
+
+test('escapeFilterPath escapes for the option and filtergraph parsers', () => {
+  assert.equal(escapeFilterPath('/tmp/luts/a.cube'), '/tmp/luts/a.cube');
+  assert.equal(escapeFilterPath('C:/temp/a.cube'), 'C\\\\:/temp/a.cube');
+  assert.equal(escapeFilterPath('/tmp/a,b;c[d]'), '/tmp/a\\,b\\;c\\[d\\]');
+  assert.equal(escapeFilterPath("/tmp/it's"), "/tmp/it\\\\\\'s");
+});
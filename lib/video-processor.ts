@@ -2,51 +2,95 @@ import { spawn } from 'child_process';
 import path from 'path';
 import fs from 'fs';
 import { generateUUID } from './utils';
-import { put } from '@vercel/blob';
+import { createHttpClient, HttpClient } from './http-client';
+import { MediaKind, sniffMediaFile } from './media-sniff';
+import { BlockedUrlError, createUrlGuard, UrlGuard } from './url-guard';
+import { contentTypeFor } from './media-types';
+import { cubeLutSize, MAX_LUT_BYTES } from './lut';
+import { writeZip } from './zip';
+import { createLocalStorage, createVercelBlobStorage, localOutputDirectory, StorageBackend, StorageQuotaError } from './storage';
 import os from 'os';
+import { Readable, Transform } from 'stream';
+import { pipeline } from 'stream/promises';
+import type { ReadableStream as NodeReadableStream } from 'stream/web';
 
 // FFmpeg setup with robust fallbacks and dynamic download
 let ffmpegPath: string;
 
+// Opt-in bootstrap for deployments without a system ffmpeg: fetch the build at
+// FFMPEG_DOWNLOAD_URL (evermeet.cx on macOS by default) and only use it if its
+// SHA-256 matches FFMPEG_DOWNLOAD_SHA256.
 async function downloadFFmpeg(): Promise<string> {
+  if (process.env.FFMPEG_AUTO_DOWNLOAD !== 'true') {
+    console.log('FFmpeg auto-download is disabled (set FFMPEG_AUTO_DOWNLOAD=true to enable)');
+    return 'ffmpeg';
+  }
+  
   const platform = os.platform();
-  const arch = os.arch();
+  const downloadUrl = process.env.FFMPEG_DOWNLOAD_URL
+    || (platform === 'darwin' ? 'https://evermeet.cx/ffmpeg/get/zip' : undefined);
+  const expectedChecksum = process.env.FFMPEG_DOWNLOAD_SHA256?.toLowerCase();
   
-  // For macOS, we can use evermeet.cx
-  if (platform === 'darwin') {
-    try {
-      console.log('Downloading FFmpeg for macOS...');
-      const downloadUrl = 'https://evermeet.cx/ffmpeg/get/zip';
-      const response = await fetch(downloadUrl);
-      
-      if (!response.ok) {
-        throw new Error('Failed to download FFmpeg');
-      }
-      
-      const buffer = await response.arrayBuffer();
-      const tempDir = path.join(os.tmpdir(), 'ffmpeg-download');
-      
-      if (!fs.existsSync(tempDir)) {
-        fs.mkdirSync(tempDir, { recursive: true });
-      }
-      
-      const zipPath = path.join(tempDir, 'ffmpeg.zip');
-      fs.writeFileSync(zipPath, new Uint8Array(buffer));
-      
-      // Extract zip (simple approach)
-      const { execSync } = require('child_process');
-      execSync(`cd "${tempDir}" && unzip -o ffmpeg.zip`, { stdio: 'pipe' });
-      
-      const ffmpegBinary = path.join(tempDir, 'ffmpeg');
-      if (fs.existsSync(ffmpegBinary)) {
-        // Make executable
-        fs.chmodSync(ffmpegBinary, 0o755);
-        console.log('Downloaded and extracted FFmpeg to:', ffmpegBinary);
-        return ffmpegBinary;
-      }
-    } catch (error) {
-      console.log('Failed to download FFmpeg:', error);
+  if (!downloadUrl) {
+    console.log(`No FFmpeg download URL configured for ${platform}/${os.arch()}, set FFMPEG_DOWNLOAD_URL`);
+    return 'ffmpeg';
+  }
+  if (!expectedChecksum) {
+    console.log('Refusing to download FFmpeg without FFMPEG_DOWNLOAD_SHA256 to verify it against');
+    return 'ffmpeg';
+  }
+  
+  const tempDir = process.env.FFMPEG_DOWNLOAD_DIR || path.join(os.tmpdir(), 'ffmpeg-download');
+  const ffmpegBinary = path.join(tempDir, 'ffmpeg');
+  
+  // Reuse a build fetched by an earlier start
+  if (fs.existsSync(ffmpegBinary)) {
+    return ffmpegBinary;
+  }
+  
+  try {
+    console.log('Downloading FFmpeg from', downloadUrl);
+    const response = await fetch(downloadUrl);
+    
+    if (!response.ok) {
+      throw new Error(`Failed to download FFmpeg: HTTP ${response.status}`);
+    }
+    
+    const buffer = new Uint8Array(await response.arrayBuffer());
+    const { createHash } = require('crypto');
+    const checksum = createHash('sha256').update(buffer).digest('hex');
+    
+    if (checksum !== expectedChecksum) {
+      throw new Error(`FFmpeg download checksum mismatch: expected ${expectedChecksum}, got ${checksum}`);
+    }
+    
+    if (!fs.existsSync(tempDir)) {
+      fs.mkdirSync(tempDir, { recursive: true });
+    }
+    
+    const archiveName = downloadUrl.endsWith('.tar.xz') ? 'ffmpeg.tar.xz' : 'ffmpeg.zip';
+    const archivePath = path.join(tempDir, archiveName);
+    fs.writeFileSync(archivePath, buffer);
+    
+    const { execFileSync } = require('child_process');
+    if (archiveName === 'ffmpeg.tar.xz') {
+      // Static Linux builds unpack into a versioned directory
+      execFileSync('tar', ['-xJf', archivePath, '-C', tempDir, '--strip-components=1'], { stdio: 'pipe' });
+    } else {
+      execFileSync('unzip', ['-o', archivePath, '-d', tempDir], { stdio: 'pipe' });
+    }
+    fs.unlinkSync(archivePath);
+    
+    if (fs.existsSync(ffmpegBinary)) {
+      // Make executable
+      fs.chmodSync(ffmpegBinary, 0o755);
+      console.log('Downloaded and verified FFmpeg at:', ffmpegBinary);
+      return ffmpegBinary;
     }
+    
+    console.log('FFmpeg archive did not contain an ffmpeg binary at its root');
+  } catch (error) {
+    console.log('Failed to download FFmpeg:', error);
   }
   
   return 'ffmpeg'; // Fallback to system
@@ -132,7 +176,7 @@ async function ensureFFmpeg(): Promise<string> {
       console.log('System FFmpeg is available');
       return ffmpegPath;
     } catch (error) {
-      console.log('System FFmpeg not available, attempting download...');
+      console.log('System FFmpeg not available, trying auto-download...');
       const downloadedPath = await downloadFFmpeg();
       ffmpegPath = downloadedPath;
       return downloadedPath;
@@ -142,15 +186,718 @@ async function ensureFFmpeg(): Promise<string> {
   return ffmpegPath;
 }
 
+export interface FFmpegCapabilities {
+  version: string;
+  encoders: Set<string>;
+  decoders: Set<string>;
+  filters: Set<string>;
+}
+
+interface Requirements {
+  filters?: string[];
+  encoders?: string[];
+  decoders?: string[];
+}
+
+interface OperationRequirements extends Requirements {
+  // Source kinds the edit makes sense for; video only when omitted
+  media?: MediaKind[];
+  // Extra needs of individual presets, algorithms or modes, checked only
+  // when that one is asked for
+  variants?: Record<string, Requirements>;
+}
+
+// Filters and encoders each edit depends on. Static builds vary (drawtext is
+// often missing without fontconfig), so edits are gated on these up front
+// rather than failing deep inside ffmpeg with a cryptic stderr.
+const OPERATION_REQUIREMENTS: Record<string, OperationRequirements> = {
+  trimVideo: { media: ['video', 'audio'] },
+  adjustSpeed: { filters: ['setpts', 'atempo'] },
+  adjustBrightness: { filters: ['eq'], media: ['video', 'image'] },
+  addText: { filters: ['drawtext'], media: ['video', 'image'] },
+  cropVideo: { filters: ['crop'], media: ['video', 'image'] },
+  rotateVideo: { filters: ['transpose', 'hflip', 'vflip'], media: ['video', 'image'] },
+  adjustVolume: { filters: ['volume'], media: ['video', 'audio'] },
+  applyFilter: {
+    media: ['video', 'image'],
+    variants: {
+      grayscale: { filters: ['colorchannelmixer'] },
+      sepia: { filters: ['colorchannelmixer'] },
+      blur: { filters: ['gblur'] },
+      sharpen: { filters: ['unsharp'] },
+      cinematic: { filters: ['eq', 'vignette', 'noise'] },
+    },
+  },
+  resizeMedia: { filters: ['scale'], media: ['video', 'image'] },
+  convertImage: { media: ['image'] },
+  normalizeAudio: { filters: ['loudnorm'], media: ['video', 'audio'] },
+  fadeAudio: { filters: ['afade'], media: ['video', 'audio'] },
+  transcodeAudio: { media: ['video', 'audio'] },
+  renderWaveform: { filters: ['showwavespic'], media: ['video', 'audio'] },
+  setChapters: { media: ['video', 'audio'] },
+  replaceAudio: { encoders: ['aac'] },
+  createSlideshow: { filters: ['scale', 'pad', 'concat'], media: ['image'] },
+  reframe: { filters: ['split', 'scale', 'crop', 'gblur', 'overlay'], media: ['video', 'image'] },
+  addWatermark: { filters: ['colorchannelmixer', 'scale2ref', 'overlay'], media: ['video', 'image'] },
+  addAnimatedOverlay: {
+    filters: ['colorchannelmixer', 'scale2ref', 'overlay'],
+    // Transparent WebM overlays, decoded with libvpx to keep their alpha
+    variants: {
+      vp8: { decoders: ['libvpx'] },
+      vp9: { decoders: ['libvpx-vp9'] },
+    },
+  },
+  composeGrid: { filters: ['scale', 'pad', 'hstack', 'vstack', 'xstack', 'amix'] },
+  concatVideos: {
+    filters: ['scale', 'pad', 'concat', 'aresample', 'anullsrc'],
+    variants: { transitions: { filters: ['xfade', 'acrossfade'] } },
+  },
+  splitVideo: { media: ['video', 'audio'] },
+  extractClips: { filters: ['trim', 'atrim', 'concat'], media: ['video', 'audio'] },
+  reverseVideo: { filters: ['reverse', 'areverse'], media: ['video', 'audio'] },
+  createBoomerang: { filters: ['trim', 'reverse', 'concat', 'loop'] },
+  freezeFrame: { filters: ['trim', 'tpad', 'concat', 'atrim', 'adelay'] },
+  rampSpeed: { filters: ['trim', 'setpts', 'atrim', 'atempo', 'concat'] },
+  slowMotion: { filters: ['minterpolate', 'setpts', 'atempo'] },
+  changeFps: {
+    variants: {
+      drop: { filters: ['fps'] },
+      interpolate: { filters: ['minterpolate'] },
+    },
+  },
+  deinterlace: {
+    variants: {
+      yadif: { filters: ['yadif'] },
+      bwdif: { filters: ['bwdif'] },
+    },
+  },
+  denoise: {
+    media: ['video', 'image'],
+    variants: {
+      hqdn3d: { filters: ['hqdn3d'] },
+      nlmeans: { filters: ['nlmeans'] },
+    },
+  },
+  sharpen: { filters: ['unsharp'], media: ['video', 'image'] },
+  stabilize: { filters: ['vidstabdetect', 'vidstabtransform'] },
+  addVignette: { filters: ['vignette'], media: ['video', 'image'] },
+  addGrain: { filters: ['noise'] },
+  obscureRegion: { filters: ['split', 'crop', 'boxblur', 'scale', 'overlay'], media: ['video', 'image'] },
+  chromaKey: { filters: ['scale2ref', 'chromakey', 'overlay'] },
+  applyLut: { filters: ['lut3d'], media: ['video', 'image'] },
+  colorAdjust: { filters: ['eq', 'hue', 'curves'], media: ['video', 'image'] },
+  whiteBalance: {
+    media: ['video', 'image'],
+    variants: {
+      auto: { filters: ['signalstats', 'metadata', 'colorchannelmixer'] },
+      temperature: { filters: ['colortemperature'] },
+      tint: { filters: ['colorbalance'] },
+    },
+  },
+};
+
+export interface OverlayPlacement {
+  position?: 'top-left' | 'top-right' | 'bottom-left' | 'bottom-right' | 'center';
+  // Pixel offset of the overlay's top-left corner; overrides position
+  x?: number;
+  y?: number;
+  // Overlay width as a fraction of the frame width
+  scale?: number;
+  // 0 (invisible) to 1 (opaque)
+  opacity?: number;
+  // Distance from the frame edges for the corner positions, in pixels
+  margin?: number;
+  // Seconds; the overlay shows for the whole video when both are omitted
+  startTime?: number;
+  endTime?: number;
+}
+
+// Filtergraph putting input 1 over input 0 per the placement;
+// `overlayOptions` are extra options for the overlay filter itself.
+function overlayGraph(placement: OverlayPlacement, overlayOptions: string = ''): string {
+  const { position = 'bottom-right', scale = 0.15, opacity = 1, margin = 20, startTime, endTime } = placement;
+  
+  const corners: Record<string, string> = {
+    'top-left': `${margin}:${margin}`,
+    'top-right': `W-w-${margin}:${margin}`,
+    'bottom-left': `${margin}:H-h-${margin}`,
+    'bottom-right': `W-w-${margin}:H-h-${margin}`,
+    'center': '(W-w)/2:(H-h)/2',
+  };
+  const xy = placement.x !== undefined || placement.y !== undefined
+    ? `${placement.x ?? 0}:${placement.y ?? 0}`
+    : corners[position];
+  
+  let enable = '';
+  if (startTime !== undefined && endTime !== undefined) enable = `:enable='between(t,${startTime},${endTime})'`;
+  else if (startTime !== undefined) enable = `:enable='gte(t,${startTime})'`;
+  else if (endTime !== undefined) enable = `:enable='lte(t,${endTime})'`;
+  
+  return (
+    `[1:v]format=rgba,colorchannelmixer=aa=${opacity}[mark];` +
+    // Sized against the frame: in scale2ref iw/ih are the reference (the
+    // video) and mdar the overlay's own display aspect ratio, which is kept
+    `[mark][0:v]scale2ref=w='iw*${scale}':h='ow/mdar'[scaled][base];` +
+    `[base][scaled]overlay=${xy}${enable}${overlayOptions},format=yuv420p[v]`
+  );
+}
+
+export interface Transition {
+  style: keyof typeof TRANSITION_STYLES;
+  // Seconds of overlap between the two clips
+  duration: number;
+}
+
+// Transition names offered to users, mapped to xfade's own
+const TRANSITION_STYLES = {
+  'fade': 'fade',
+  'dissolve': 'dissolve',
+  'wipe': 'wipeleft',
+  'slide': 'slideleft',
+  'fade-to-black': 'fadeblack',
+} as const;
+
+// Longest stretch the reverse filter is given to hold in memory at once
+const REVERSE_CHUNK_SECONDS = 10;
+
+export interface SpeedPoint {
+  // Seconds into the source
+  time: number;
+  speed: number;
+}
+
+// Ramps are approximated by steps of constant speed this long (source seconds)
+const RAMP_STEP_SECONDS = 0.25;
+
+// atempo takes 0.5-2.0 per instance, so larger changes chain several
+export function atempoChain(speed: number): string {
+  const factors: number[] = [];
+  let remaining = speed;
+  while (remaining < 0.5) {
+    factors.push(0.5);
+    remaining /= 0.5;
+  }
+  while (remaining > 2) {
+    factors.push(2);
+    remaining /= 2;
+  }
+  factors.push(remaining);
+  return factors.map((factor) => `atempo=${factor}`).join(',');
+}
+
+// minterpolate settings for slowMotion, cheapest first. blend cross-fades
+// neighbouring frames; the others estimate motion and draw new frames.
+export const INTERPOLATION_QUALITIES = {
+  fast: 'mi_mode=blend',
+  balanced: 'mi_mode=mci:mc_mode=obmc:me_mode=bilat',
+  high: 'mi_mode=mci:mc_mode=aobmc:me_mode=bidir:vsbmc=1',
+} as const;
+
+export type InterpolationQuality = keyof typeof INTERPOLATION_QUALITIES;
+
+// Named frame rates for changeFps
+export const FPS_PRESETS = {
+  cinematic: 24,
+} as const;
+
+export type FpsPreset = keyof typeof FPS_PRESETS;
+
+// Filter settings per denoise strength. hqdn3d takes spatial and temporal
+// strengths for luma and chroma; nlmeans a single denoising strength.
+const DENOISE_STRENGTHS = {
+  light: { hqdn3d: '2:1.5:3:2.25', nlmeans: 's=1.5' },
+  medium: { hqdn3d: '4:3:6:4.5', nlmeans: 's=3' },
+  strong: { hqdn3d: '8:6:12:9', nlmeans: 's=6' },
+} as const;
+
+export type DenoiseStrength = keyof typeof DENOISE_STRENGTHS;
+
+// One-word looks for applyFilter; colorAdjust covers fine-grained changes
+export type FilterPreset = 'grayscale' | 'sepia' | 'blur' | 'sharpen' | 'cinematic';
+
+// Every field is optional and neutral when left out
+export interface ColorAdjustments {
+  // 0 (flat grey) to 2, 1 unchanged
+  contrast?: number;
+  // -1 (black) to 1 (white), 0 unchanged
+  brightness?: number;
+  // 0 (black and white) to 3, 1 unchanged
+  saturation?: number;
+  // 0.1 to 10, 1 unchanged; above 1 brightens midtones
+  gamma?: number;
+  // Hue rotation in degrees, -180 to 180
+  hue?: number;
+  // -1 to 1: lift (positive) or crush (negative) the darks and the brights
+  shadows?: number;
+  highlights?: number;
+}
+
+export const COLOR_ADJUSTMENT_RANGES: Record<keyof ColorAdjustments, [number, number]> = {
+  contrast: [0, 2],
+  brightness: [-1, 1],
+  saturation: [0, 3],
+  gamma: [0.1, 10],
+  hue: [-180, 180],
+  shadows: [-1, 1],
+  highlights: [-1, 1],
+};
+
+// The filter chain for a set of adjustments, or null when all are neutral
+function colorAdjustFilter(adjustments: ColorAdjustments): string | null {
+  const { contrast, brightness, saturation, gamma, hue, shadows, highlights } = adjustments;
+  const filters: string[] = [];
+  
+  const eq = Object.entries({ contrast, brightness, saturation, gamma })
+    .filter(([, value]) => value !== undefined)
+    .map(([name, value]) => `${name}=${value}`);
+  if (eq.length > 0) {
+    filters.push(`eq=${eq.join(':')}`);
+  }
+  if (hue) {
+    filters.push(`hue=h=${hue}`);
+  }
+  if (shadows || highlights) {
+    // Moves the quarter and three-quarter points of the tone curve by up to 0.15
+    const low = (0.25 + 0.15 * (shadows ?? 0)).toFixed(3);
+    const high = (0.75 + 0.15 * (highlights ?? 0)).toFixed(3);
+    filters.push(`curves=all='0/0 0.25/${low} 0.75/${high} 1/1'`);
+  }
+  
+  return filters.length > 0 ? filters.join(',') : null;
+}
+
+export interface WhiteBalance {
+  // Neutralize the average color of sampled frames first (gray world)
+  auto?: boolean;
+  // Kelvin, 1000 to 40000; 6500 is neutral, lower warms and higher cools
+  temperature?: number;
+  // -1 (green) to 1 (magenta), 0 neutral
+  tint?: number;
+}
+
+// Frames averaged to estimate the gray point for auto white balance
+const WHITE_BALANCE_SAMPLES = 8;
+
+// Building blocks for looks, shared by the standalone effects and the
+// applyFilter presets. Intensity runs from 0 (none) to 1 (strong).
+function vignetteFilter(intensity: number): string {
+  // The lens angle sets how far in the darkening reaches; PI/2 is the most
+  return `vignette=angle=${(0.1 + intensity * (Math.PI / 2 - 0.1)).toFixed(3)}`;
+}
+
+function grainFilter(intensity: number): string {
+  // Temporal, uniform noise on every plane reads as film grain rather than
+  // a static pattern
+  return `noise=alls=${Math.round(intensity * 30)}:allf=t+u`;
+}
+
+export interface Region {
+  x: number;
+  y: number;
+  width: number;
+  height: number;
+}
+
+// LUTs kept on disk by cachedLut, and how recently used one must be to be
+// safe from eviction regardless of that limit
+const LUT_CACHE_ENTRIES = 50;
+const LUT_CACHE_GRACE_MS = 10 * 60 * 1000;
+
+// Output frame sizes for reframe, 1080 pixels on the short side
+const REFRAME_CANVASES = {
+  '9:16': [1080, 1920],
+  '1:1': [1080, 1080],
+  '4:5': [1080, 1350],
+  '16:9': [1920, 1080],
+} as const;
+
+const IMAGE_EXTENSIONS = ['jpg', 'jpeg', 'png', 'webp', 'gif'];
+const AUDIO_EXTENSIONS = ['mp3', 'wav', 'm4a', 'aac'];
+
+function extensionOf(fileName: string): string {
+  return path.extname(fileName).slice(1).toLowerCase();
+}
+
+function mediaKindOf(fileName: string): MediaKind {
+  const extension = extensionOf(fileName);
+  if (IMAGE_EXTENSIONS.includes(extension)) return 'image';
+  if (AUDIO_EXTENSIONS.includes(extension)) return 'audio';
+  return 'video';
+}
+
+// HLS (.m3u8) and DASH (.mpd) manifests
+function isPlaylistUrl(url: string): boolean {
+  try {
+    const pathname = new URL(url).pathname.toLowerCase();
+    return pathname.endsWith('.m3u8') || pathname.endsWith('.mpd');
+  } catch {
+    return false;
+  }
+}
+
+export interface MediaInfo {
+  durationSeconds?: number;
+  fps?: number;
+  videoCodec?: string;
+  width?: number;
+  height?: number;
+  audioCodec?: string;
+  sampleRate?: number;
+  // The video stream declares a field order (e.g. "top first")
+  interlaced?: boolean;
+}
+
+// Seconds, an "HH:MM:SS.mmm" / "MM:SS.mmm" timecode, or a frame number like "250f"
+export type TimeInput = number | string;
+
+export function parseTimeInput(value: TimeInput, fps?: number): number {
+  if (typeof value === 'number') {
+    return value;
+  }
+  
+  const trimmed = value.trim();
+  const frame = trimmed.match(/^(\d+)f$/i);
+  if (frame) {
+    if (!fps) {
+      throw new ProcessingError(`Cannot convert frame ${trimmed} to a time without the source frame rate`, 'ffmpeg', false);
+    }
+    return Number(frame[1]) / fps;
+  }
+  
+  if (/^\d+(:\d{1,2}){0,2}(\.\d+)?$/.test(trimmed)) {
+    return trimmed.split(':').reduce((total, part) => total * 60 + Number(part), 0);
+  }
+  
+  throw new ProcessingError(`Invalid time "${value}": use seconds, HH:MM:SS.mmm, or a frame number like 250f`, 'ffmpeg', false);
+}
+
+export interface Chapter {
+  start: number;
+  title: string;
+}
+
+// A concat demuxer list entry; quotes inside the path close the quoted
+// string, add an escaped quote and reopen it
+export function concatListEntry(filePath: string): string {
+  return `file '${filePath.replace(/'/g, "'\\''")}'`;
+}
+
+// A file path as a filter option value. The option parser treats \ ' and :
+// as special and the filtergraph parser \ ' [ ] , and ;, so the path is
+// escaped for the first and the result again for the second.
+export function escapeFilterPath(filePath: string): string {
+  return filePath
+    .replace(/[\\':]/g, '\\$&')
+    .replace(/[\\'[\],;]/g, '\\$&');
+}
+
+// Special characters in ffmetadata values are backslash-escaped
+function escapeMetadata(value: string): string {
+  return value.replace(/([=;#\\\n])/g, '\\$1');
+}
+
+function usesFrames(...values: TimeInput[]): boolean {
+  return values.some((value) => typeof value === 'string' && /f$/i.test(value.trim()));
+}
+
+// Listing lines look like " TSC gblur  V->V  ..." (filters) or
+// " V....D libx264  ..." (encoders): a flags column followed by the name.
+function parseListing(output: string): Set<string> {
+  const names = new Set<string>();
+  for (const line of output.split('\n')) {
+    const match = line.match(/^ [A-Z.|]{3,6} +([\w-]+) /);
+    if (match) {
+      names.add(match[1]);
+    }
+  }
+  return names;
+}
+
+let capabilities: Promise<FFmpegCapabilities> | null = null;
+
+// Called once at server start (see instrumentation.ts) so unsupported edits
+// are logged up front; later calls share the same result.
+export function detectCapabilities(): Promise<FFmpegCapabilities> {
+  if (!capabilities) {
+    capabilities = ensureFFmpeg().then((binary) => {
+      const { execFileSync } = require('child_process');
+      const run = (flag: string): string =>
+        execFileSync(binary, ['-hide_banner', flag], { encoding: 'utf8', stdio: ['ignore', 'pipe', 'pipe'] });
+      
+      const detected: FFmpegCapabilities = {
+        version: run('-version').split('\n')[0].trim(),
+        encoders: parseListing(run('-encoders')),
+        decoders: parseListing(run('-decoders')),
+        filters: parseListing(run('-filters')),
+      };
+      
+      console.log('Detected FFmpeg:', detected.version);
+      for (const [operation, { missing, variants }] of Object.entries(missingRequirements(detected))) {
+        if (missing.length > 0) {
+          console.log(`Disabling ${operation}, FFmpeg build is missing: ${missing.join(', ')}`);
+        }
+        for (const [variant, variantMissing] of Object.entries(variants)) {
+          if (variantMissing.length > 0) {
+            console.log(`Disabling ${operation} (${variant}), FFmpeg build is missing: ${variantMissing.join(', ')}`);
+          }
+        }
+      }
+      
+      return detected;
+    });
+    
+    // Let a later call retry detection if ffmpeg could not be run at all
+    capabilities.catch(() => {
+      capabilities = null;
+    });
+  }
+  
+  return capabilities;
+}
+
+function missingFrom(detected: FFmpegCapabilities, requirements: Requirements): string[] {
+  return [
+    ...(requirements.filters ?? []).filter((name) => !detected.filters.has(name)).map((name) => `${name} filter`),
+    ...(requirements.encoders ?? []).filter((name) => !detected.encoders.has(name)).map((name) => `${name} encoder`),
+    ...(requirements.decoders ?? []).filter((name) => !detected.decoders.has(name)).map((name) => `${name} decoder`),
+  ];
+}
+
+// What each operation, and each of its variants, lacks in this build
+function missingRequirements(detected: FFmpegCapabilities): Record<string, { missing: string[]; variants: Record<string, string[]> }> {
+  return Object.fromEntries(
+    Object.entries(OPERATION_REQUIREMENTS).map(([operation, requirements]) => [operation, {
+      missing: missingFrom(detected, requirements),
+      variants: Object.fromEntries(
+        Object.entries(requirements.variants ?? {}).map(([variant, needs]) => [variant, missingFrom(detected, needs)])
+      ),
+    }])
+  );
+}
+
+export interface StageTimings {
+  downloadMs: number;
+  processMs: number;
+  uploadMs: number;
+  totalMs: number;
+}
+
+export interface EditOptions {
+  // Human-readable name the output downloads as, sanitized; outputs are
+  // named after the source file otherwise. May be a template using the
+  // placeholders in expandOutputName.
+  outputName?: string;
+  // For edits with several outputs (splits, separate clips), upload them as
+  // one zip instead of a file per part
+  bundle?: boolean;
+}
+
+// Fills {input_stem}, {operation}, {timestamp} (ms since epoch), {date}
+// (YYYY-MM-DD) and {ext} in a requested output name; unknown placeholders
+// are left as typed and later sanitized away.
+export function expandOutputName(template: string, values: { originalFilename: string; operation: string; extension: string }): string {
+  const now = new Date();
+  const placeholders: Record<string, string> = {
+    input_stem: sanitizeStem(values.originalFilename),
+    operation: values.operation,
+    timestamp: now.getTime().toString(),
+    date: now.toISOString().slice(0, 10),
+    ext: values.extension,
+  };
+  return template.replace(/\{(\w+)\}/g, (match, key: string) => placeholders[key] ?? match);
+}
+
+export interface ProcessedVideo {
+  url: string;
+  // Every output in order, for edits that produce several (url is the first)
+  urls?: string[];
+  originalFilename: string;
+  // Frame rate of the (first) output, when it is a video
+  fps?: number;
+  timings: StageTimings;
+  attempts: StageAttempts;
+}
+
+// Recovers the name the user uploaded from a source URL, dropping the
+// timestamp prefix added on upload (see multimodal-input and api/files/upload).
+export function originalFilenameFromUrl(videoUrl: string): string {
+  try {
+    const name = decodeURIComponent(path.posix.basename(new URL(videoUrl).pathname));
+    return name.replace(/^\d{13}-/, '') || 'video.mp4';
+  } catch {
+    return 'video.mp4';
+  }
+}
+
+export function sanitizeStem(name: string): string {
+  return path.basename(name)
+    .replace(/\.[^.]*$/, '')
+    .replace(/[^a-zA-Z0-9_-]+/g, '-')
+    .replace(/^-+|-+$/g, '')
+    .slice(0, 100);
+}
+
 interface VideoProcessorOptions {
   tempDir?: string;
+  retryPolicies?: Partial<Record<RetryStage, Partial<RetryPolicy>>>;
+  // Profile for constrained containers: one edit at a time and a free-space
+  // floor on the temp dir, which may be a RAM-backed tmpfs
+  lowMemory?: boolean;
+  maxConcurrentJobs?: number;
+  // Edits waiting for a slot beyond this are turned away instead of piling up
+  maxQueuedJobs?: number;
+  minFreeTempBytes?: number;
+  // Streaming playlists are cut at this length when pulled as a source
+  maxPlaylistSeconds?: number;
+  // Sources over these limits are refused before ffmpeg runs; 0 disables a limit
+  maxInputBytes?: number;
+  maxInputSeconds?: number;
+  // Directories file:// sources may be read from in place; local inputs are
+  // refused when empty
+  localInputRoots?: string[];
+  httpClient?: HttpClient;
+  urlGuard?: UrlGuard;
+  // ffmpeg fetches playlist variants and segments itself, out of the URL
+  // guard's reach, so manifests are only accepted when sources are already
+  // trusted: an allowed hosts list is set or private addresses are allowed
+  allowPlaylists?: boolean;
+  // Where outputs are uploaded; VIDEO_STORAGE_BACKEND picks one otherwise
+  storage?: StorageBackend;
+}
+
+type FailureKind = 'network' | 'ffmpeg' | 'storage';
+
+type RetryStage = 'download' | 'process' | 'upload';
+
+export type StageAttempts = Record<RetryStage, number>;
+
+interface RetryPolicy {
+  retries: number;
+  baseDelayMs: number;
+  maxDelayMs: number;
+  // Fraction (0-1) of each backoff delay that may be randomly skipped
+  jitter: number;
+}
+
+// Failures are classified so that only transient ones (a CDN hiccup, a flaky
+// storage write) are retried; a bad filtergraph fails the same way every time.
+// The message is meant to be shown to the user; bulky diagnostics such as
+// ffmpeg's full output go in details, which is only logged.
+export class ProcessingError extends Error {
+  kind: FailureKind;
+  transient: boolean;
+  details?: string;
+
+  constructor(message: string, kind: FailureKind, transient: boolean, details?: string) {
+    super(message);
+    this.name = 'ProcessingError';
+    this.kind = kind;
+    this.transient = transient;
+    this.details = details;
+  }
+}
+
+function formatMegabytes(bytes: number): string {
+  return `${Math.round(bytes / (1024 * 1024))}MB`;
+}
+
+function isTransientStatus(status: number): boolean {
+  return status === 408 || status === 429 || status >= 500;
+}
+
+// Unset and blank variables (as left by .env.example) both fall back
+// A typo here would otherwise become NaN, which silently disables limits
+// and makes retry counts never run out, so it fails loudly instead
+function envNumber(name: string, fallback: number): number {
+  const value = process.env[name]?.trim();
+  if (!value) {
+    return fallback;
+  }
+  const parsed = Number(value);
+  if (!Number.isFinite(parsed)) {
+    throw new Error(`${name} must be a number, got "${value}"`);
+  }
+  return parsed;
+}
+
+// Each stage reads VIDEO_<STAGE>_RETRIES, falling back to VIDEO_MAX_RETRIES;
+// the backoff settings are shared across stages.
+function retryPolicy(stage: string, defaultRetries: number, overrides: Partial<RetryPolicy> = {}): RetryPolicy {
+  return {
+    retries: envNumber(`VIDEO_${stage}_RETRIES`, envNumber('VIDEO_MAX_RETRIES', defaultRetries)),
+    baseDelayMs: envNumber('VIDEO_RETRY_BASE_MS', 500),
+    maxDelayMs: envNumber('VIDEO_RETRY_MAX_MS', 10000),
+    jitter: envNumber('VIDEO_RETRY_JITTER', 0.5),
+    ...overrides,
+  };
+}
+
+function sleep(ms: number): Promise<void> {
+  return new Promise((resolve) => setTimeout(resolve, ms));
 }
 
 class VideoProcessor {
   private tempDir: string;
+  private tempDirConfigured: boolean;
+  private retryPolicies: Record<RetryStage, RetryPolicy>;
+  private maxConcurrentJobs: number;
+  private maxQueuedJobs: number;
+  private minFreeTempBytes: number;
+  private maxPlaylistSeconds: number;
+  private maxInputBytes: number;
+  private maxInputSeconds: number;
+  private localInputRoots: string[];
+  private httpClient: HttpClient;
+  private urlGuard: UrlGuard;
+  private allowPlaylists: boolean;
+  private storage: StorageBackend;
+  private activeJobs = 0;
+  // Cached LUT paths in use by running edits, with how many hold each
+  private lutsInUse = new Map<string, number>();
+  private waitingJobs: Array<() => void> = [];
 
   constructor(options: VideoProcessorOptions = {}) {
-    this.tempDir = options.tempDir || path.join(process.cwd(), 'tmp');
+    const lowMemory = options.lowMemory ?? process.env.VIDEO_LOW_MEMORY === 'true';
+    
+    this.tempDirConfigured = Boolean(options.tempDir || process.env.VIDEO_TEMP_DIR);
+    this.tempDir = options.tempDir || process.env.VIDEO_TEMP_DIR || path.join(process.cwd(), 'tmp');
+    this.retryPolicies = {
+      download: retryPolicy('DOWNLOAD', 3, options.retryPolicies?.download),
+      // ffmpeg only fails transiently when killed, so one more try is plenty
+      process: retryPolicy('PROCESS', 1, options.retryPolicies?.process),
+      upload: retryPolicy('UPLOAD', 3, options.retryPolicies?.upload),
+    };
+    this.maxConcurrentJobs = options.maxConcurrentJobs ?? envNumber('VIDEO_MAX_CONCURRENT_JOBS', lowMemory ? 1 : os.cpus().length);
+    this.maxQueuedJobs = options.maxQueuedJobs ?? envNumber('VIDEO_MAX_QUEUED_JOBS', 20);
+    this.minFreeTempBytes = options.minFreeTempBytes ?? envNumber('VIDEO_TEMP_MIN_FREE_MB', lowMemory ? 256 : 0) * 1024 * 1024;
+    this.maxPlaylistSeconds = options.maxPlaylistSeconds ?? envNumber('VIDEO_MAX_PLAYLIST_SECONDS', 3600);
+    this.maxInputBytes = options.maxInputBytes ?? envNumber('VIDEO_MAX_INPUT_MB', 2048) * 1024 * 1024;
+    this.maxInputSeconds = options.maxInputSeconds ?? envNumber('VIDEO_MAX_INPUT_SECONDS', 7200);
+    this.storage = options.storage ?? (process.env.VIDEO_STORAGE_BACKEND === 'local'
+      ? createLocalStorage({
+        directory: localOutputDirectory(),
+        baseUrl: process.env.VIDEO_PUBLIC_BASE_URL || 'http://localhost:3000',
+        urlPrefix: process.env.VIDEO_OUTPUT_URL_PREFIX || '/processed',
+        quotaBytes: envNumber('VIDEO_LOCAL_OUTPUT_QUOTA_MB', 0) * 1024 * 1024,
+      })
+      : createVercelBlobStorage({
+        token: process.env.BLOB_READ_WRITE_TOKEN,
+        cacheMaxAgeSeconds: envNumber('VIDEO_BLOB_CACHE_MAX_AGE_SECONDS', 30 * 24 * 3600),
+      }));
+    const allowedHosts = (process.env.VIDEO_ALLOWED_HOSTS ?? '').split(',').map((host) => host.trim()).filter(Boolean);
+    const allowPrivateAddresses = process.env.VIDEO_ALLOW_PRIVATE_ADDRESSES === 'true';
+    this.urlGuard = options.urlGuard ?? createUrlGuard({ allowedHosts, allowPrivateAddresses });
+    this.allowPlaylists = options.allowPlaylists ?? (allowedHosts.length > 0 || allowPrivateAddresses);
+    this.httpClient = options.httpClient ?? createHttpClient({
+      headersTimeoutMs: envNumber('VIDEO_HTTP_HEADERS_TIMEOUT_MS', 30000),
+      idleTimeoutMs: envNumber('VIDEO_HTTP_IDLE_TIMEOUT_MS', 60000),
+      maxRedirects: envNumber('VIDEO_HTTP_MAX_REDIRECTS', 5),
+      urlGuard: this.urlGuard,
+    });
+    this.localInputRoots = options.localInputRoots
+      ?? (process.env.VIDEO_ALLOW_LOCAL_INPUTS === 'true'
+        ? (process.env.VIDEO_LOCAL_INPUT_ROOTS ?? '').split(',').map((root) => root.trim()).filter(Boolean)
+        : []);
     this.ensureTempDir();
   }
 
@@ -161,163 +908,952 @@ class VideoProcessor {
       }
     } catch (error) {
       console.error('Failed to create temp directory:', error);
+      
+      // An explicitly configured dir is usually the one writable volume on a
+      // read-only root filesystem, so don't quietly write somewhere else
+      if (this.tempDirConfigured) {
+        throw error;
+      }
+      
       // Fallback to system temp directory
-      this.tempDir = require('os').tmpdir();
+      this.tempDir = os.tmpdir();
     }
   }
 
-  private async downloadVideo(url: string): Promise<string> {
-    const videoId = generateUUID();
-    const tempPath = path.join(this.tempDir, `input_${videoId}.mp4`);
-    
-    // Download the video file from the URL
-    const response = await fetch(url);
-    if (!response.ok) {
-      throw new Error('Failed to download video');
+  private ensureTempSpace() {
+    if (this.minFreeTempBytes <= 0) {
+      return;
     }
     
-    const buffer = await response.arrayBuffer();
-    fs.writeFileSync(tempPath, new Uint8Array(buffer));
-    
-    return tempPath;
+    const stats = fs.statfsSync(this.tempDir);
+    const freeBytes = stats.bavail * stats.bsize;
+    if (freeBytes < this.minFreeTempBytes) {
+      throw new ProcessingError(
+        `Not enough free space in ${this.tempDir}: ${Math.round(freeBytes / 1024 / 1024)}MB available, ${Math.round(this.minFreeTempBytes / 1024 / 1024)}MB required`,
+        'storage',
+        false
+      );
+    }
   }
 
-  private async runFFmpeg(args: string[]): Promise<string> {
-    // Ensure FFmpeg is available before running
-    const currentFFmpegPath = await ensureFFmpeg();
+  private async acquireJobSlot(): Promise<void> {
+    if (this.activeJobs < this.maxConcurrentJobs) {
+      this.activeJobs++;
+      return;
+    }
     
-    return new Promise((resolve, reject) => {
-      console.log('Running FFmpeg with path:', currentFFmpegPath);
-      console.log('FFmpeg arguments:', args);
-      
-      // Check if ffmpeg path exists (for static binaries)
-      if (currentFFmpegPath !== 'ffmpeg' && !fs.existsSync(currentFFmpegPath)) {
-        reject(new Error(`FFmpeg binary not found at path: ${currentFFmpegPath}`));
-        return;
-      }
-      
-      const process = spawn(currentFFmpegPath, args);
-      
-      let stderr = '';
-      let stdout = '';
-      
-      process.stdout.on('data', (data) => {
-        stdout += data.toString();
-      });
-      
-      process.stderr.on('data', (data) => {
-        stderr += data.toString();
-      });
-      
-      process.on('close', (code) => {
-        console.log('FFmpeg process finished with code:', code);
-        console.log('FFmpeg stderr:', stderr);
-        console.log('FFmpeg stdout:', stdout);
-        
-        if (code === 0) {
-          resolve(stderr);
-        } else {
-          reject(new Error(`FFmpeg process exited with code ${code}. stderr: ${stderr}, stdout: ${stdout}`));
-        }
-      });
-      
-      process.on('error', (error) => {
-        console.error('FFmpeg spawn error:', error);
-        reject(new Error(`FFmpeg spawn error: ${error.message}. Path: ${currentFFmpegPath}`));
-      });
-    });
+    if (this.waitingJobs.length >= this.maxQueuedJobs) {
+      throw new ProcessingError(
+        `Too many edits in progress (${this.activeJobs} running, ${this.waitingJobs.length} queued), try again shortly`,
+        'ffmpeg',
+        false
+      );
+    }
+    
+    // The slot is handed over directly by releaseJobSlot, so activeJobs stays put
+    await new Promise<void>((resolve) => this.waitingJobs.push(resolve));
   }
 
-  private generateOutputPath(): string {
-    const outputId = generateUUID();
-    return path.join(this.tempDir, `output_${outputId}.mp4`);
+  private releaseJobSlot() {
+    const next = this.waitingJobs.shift();
+    if (next) {
+      next();
+    } else {
+      this.activeJobs--;
+    }
   }
 
-  private async uploadToVercelBlob(filePath: string): Promise<string> {
-    const buffer = fs.readFileSync(filePath);
-    const fileName = `processed_${generateUUID()}.mp4`;
-    
-    const blob = await put(fileName, buffer, {
-      access: 'public',
-      contentType: 'video/mp4',
-    });
+  private async withRetry<T>(stage: RetryStage, attempts: StageAttempts, fn: () => Promise<T>): Promise<T> {
+    const policy = this.retryPolicies[stage];
     
-    return blob.url;
+    for (let attempt = 1; ; attempt++) {
+      attempts[stage] = attempt;
+      try {
+        return await fn();
+      } catch (error) {
+        if (!(error instanceof ProcessingError) || !error.transient || attempt > policy.retries) {
+          throw error;
+        }
+        
+        // Exponential backoff, capped, with up to `jitter` of it randomly shaved
+        // off so parallel edits hitting the same blip don't retry in lockstep
+        const backoff = Math.min(policy.maxDelayMs, policy.baseDelayMs * 2 ** (attempt - 1));
+        const delay = Math.round(backoff * (1 - policy.jitter * Math.random()));
+        console.log(`${stage} failed (${error.kind}, attempt ${attempt}/${policy.retries + 1}), retrying in ${delay}ms:`, error.message);
+        await sleep(delay);
+      }
+    }
   }
 
-  async trimVideo(videoUrl: string, startTime: number, endTime: number): Promise<string> {
-    const inputPath = await this.downloadVideo(videoUrl);
-    const outputPath = this.generateOutputPath();
-    
-    const args = [
-      '-i', inputPath,
-      '-ss', startTime.toString(),
-      '-t', (endTime - startTime).toString(),
+  // Lets ffmpeg's HLS/DASH demuxer pull and stitch the segments, limited to network
+  // protocols so a playlist can't reference file: or other local sources.
+  private async downloadPlaylist(url: string, tempPath: string): Promise<void> {
+    // HLS carries AAC in ADTS frames, which MP4 can't hold as-is; DASH
+    // segments are already MP4-style
+    const isHls = new URL(url).pathname.toLowerCase().endsWith('.m3u8');
+    await this.runFFmpeg([
+      '-protocol_whitelist', 'http,https,tls,tcp,crypto',
+      '-i', url,
+      '-t', this.maxPlaylistSeconds.toString(),
+      // A live or endless stream stops just past the size limit instead of
+      // filling the disk, and is then refused below
+      ...(this.maxInputBytes > 0 ? ['-fs', (this.maxInputBytes + 1).toString()] : []),
       '-c', 'copy',
+      ...(isHls ? ['-bsf:a', 'aac_adtstoasc'] : []),
       '-y',
-      outputPath
-    ];
-    
-    await this.runFFmpeg(args);
-    
-    // Upload the processed video and return URL
-    const resultUrl = await this.uploadToVercelBlob(outputPath);
+      tempPath
+    ]);
     
-    // Clean up temp files
-    fs.unlinkSync(inputPath);
-    fs.unlinkSync(outputPath);
-    
-    return resultUrl;
+    this.ensureInputSize(fs.statSync(tempPath).size);
   }
 
-  async adjustSpeed(videoUrl: string, speed: number): Promise<string> {
-    const inputPath = await this.downloadVideo(videoUrl);
-    const outputPath = this.generateOutputPath();
+  // file:// sources are read in place when they resolve (symlinks included)
+  // inside one of the allowed roots; anything else is refused outright.
+  private resolveLocalInput(url: string): string | null {
+    if (!url.startsWith('file:')) {
+      return null;
+    }
     
-    const videoSpeed = 1 / speed; // FFmpeg uses inverse for video speed
-    const audioSpeed = speed;
+    if (this.localInputRoots.length === 0) {
+      throw new ProcessingError('Local file inputs are disabled (set VIDEO_ALLOW_LOCAL_INPUTS)', 'network', false);
+    }
     
-    const args = [
-      '-i', inputPath,
-      '-filter_complex', `[0:v]setpts=${videoSpeed}*PTS[v];[0:a]atempo=${audioSpeed}[a]`,
-      '-map', '[v]',
-      '-map', '[a]',
-      '-y',
-      outputPath
-    ];
+    const { fileURLToPath } = require('url');
+    let filePath: string;
+    try {
+      filePath = fs.realpathSync(fileURLToPath(url));
+    } catch (error: any) {
+      throw new ProcessingError(`Local input not found: ${error.message}`, 'network', false);
+    }
+    
+    const allowed = this.localInputRoots.some((root) => {
+      let resolvedRoot: string;
+      try {
+        resolvedRoot = fs.realpathSync(root);
+      } catch (error: any) {
+        throw new ProcessingError(
+          `Local input root ${root} from VIDEO_LOCAL_INPUT_ROOTS can't be used: ${error.code === 'ENOENT' ? 'it does not exist' : error.message}`,
+          'network',
+          false
+        );
+      }
+      // Inside the root when the way there doesn't climb out of it; a
+      // string prefix check breaks on a root of /
+      const relative = path.relative(resolvedRoot, filePath);
+      return relative !== '' && relative !== '..' && !relative.startsWith(`..${path.sep}`) && !path.isAbsolute(relative);
+    });
+    if (!allowed) {
+      throw new ProcessingError(`Local input ${filePath} is outside the allowed roots`, 'network', false);
+    }
+    
+    return filePath;
+  }
+
+  // Retries happen here, under the download stage's policy, so every attempt
+  // (a resume included) is counted in `attempts`
+  private async downloadVideo(url: string, attempts: StageAttempts, extension: string = 'mp4'): Promise<string> {
+    const videoId = generateUUID();
+    const tempPath = path.join(this.tempDir, `input_${videoId}.${extension}`);
+    
+    if (isPlaylistUrl(url)) {
+      // ffmpeg follows the manifest's variant and segment URIs (and their
+      // redirects) itself, where the URL guard can't see them, so a public
+      // manifest could point it at internal hosts
+      if (!this.allowPlaylists) {
+        throw new ProcessingError(
+          'HLS/DASH sources are disabled (set VIDEO_ALLOWED_HOSTS to trusted hosts to enable them)',
+          'network',
+          false
+        );
+      }
+      try {
+        await this.urlGuard(new URL(url));
+      } catch (error: any) {
+        throw new ProcessingError(`Failed to download video: ${error.message}`, 'network', false);
+      }
+      try {
+        await this.withRetry('download', attempts, () => this.downloadPlaylist(url, tempPath));
+      } catch (error) {
+        fs.rmSync(tempPath, { force: true });
+        throw error;
+      }
+      return tempPath;
+    }
+    
+    // The partial file is kept between attempts, so a retry picks up where an
+    // interrupted transfer stopped rather than pulling a multi-GB master again
+    const source: { validator?: string } = {};
+    try {
+      await this.withRetry('download', attempts, async () => {
+        try {
+          await this.fetchToFile(url, tempPath, source);
+        } catch (error) {
+          const bytesWritten = fs.existsSync(tempPath) ? fs.statSync(tempPath).size : 0;
+          if (error instanceof ProcessingError && error.transient && bytesWritten > 0) {
+            console.log(`Download interrupted after ${bytesWritten} bytes, will resume:`, error.message);
+          }
+          throw error;
+        }
+      });
+    } catch (error) {
+      fs.rmSync(tempPath, { force: true });
+      throw error;
+    }
+    return tempPath;
+  }
+
+  // Appends to a partial file with a Range request when one exists. If-Range
+  // makes the origin send the whole file instead if it changed in between.
+  private async fetchToFile(url: string, tempPath: string, source: { validator?: string }): Promise<void> {
+    const bytesWritten = fs.existsSync(tempPath) ? fs.statSync(tempPath).size : 0;
+    const headers: Record<string, string> = {};
+    if (bytesWritten > 0) {
+      headers['Range'] = `bytes=${bytesWritten}-`;
+      if (source.validator) {
+        headers['If-Range'] = source.validator;
+      }
+    }
+    
+    let response: Response;
+    try {
+      response = await this.httpClient(url, { headers });
+    } catch (error: any) {
+      // A refused destination won't become acceptable on retry
+      throw new ProcessingError(`Failed to download video: ${error.message}`, 'network', !(error instanceof BlockedUrlError));
+    }
+    
+    if (!response.ok) {
+      throw new ProcessingError(
+        `Failed to download video: HTTP ${response.status}`,
+        'network',
+        isTransientStatus(response.status)
+      );
+    }
+    
+    if (!response.body) {
+      throw new ProcessingError('Failed to download video: empty response body', 'network', true);
+    }
+    
+    // Weak ETags aren't allowed in If-Range, Last-Modified is
+    const etag = response.headers.get('etag');
+    source.validator = (etag && !etag.startsWith('W/') ? etag : response.headers.get('last-modified')) ?? undefined;
+    
+    // A 200 to a range request means the origin is sending everything again
+    const append = bytesWritten > 0 && response.status === 206;
+    
+    // The declared size turns an oversized source away before any of it is
+    // written; Content-Range carries the full size on a resumed transfer
+    const totalBytes = append
+      ? Number(response.headers.get('content-range')?.split('/')[1])
+      : Number(response.headers.get('content-length'));
+    if (totalBytes > 0) {
+      this.ensureInputSize(totalBytes);
+    }
+    
+    // Origins may omit or understate the length, so the stream is counted too
+    let received = append ? bytesWritten : 0;
+    const sizeGuard = new Transform({
+      transform: (chunk: Buffer, _encoding, callback) => {
+        received += chunk.length;
+        try {
+          this.ensureInputSize(received);
+          callback(null, chunk);
+        } catch (error: any) {
+          callback(error);
+        }
+      },
+    });
+    
+    // Stream to disk in small chunks so multi-GB sources never sit in memory
+    try {
+      await pipeline(
+        Readable.fromWeb(response.body as unknown as NodeReadableStream),
+        sizeGuard,
+        fs.createWriteStream(tempPath, { flags: append ? 'a' : 'w', highWaterMark: 1024 * 1024 })
+      );
+    } catch (error: any) {
+      if (error instanceof ProcessingError) {
+        throw error;
+      }
+      throw new ProcessingError(`Failed to download video: ${error.message}`, 'network', true);
+    }
+  }
+
+  private ensureInputSize(bytes: number) {
+    if (this.maxInputBytes > 0 && bytes > this.maxInputBytes) {
+      throw new ProcessingError(
+        `Input too large (413): over the ${formatMegabytes(this.maxInputBytes)} limit (VIDEO_MAX_INPUT_MB)`,
+        'network',
+        false
+      );
+    }
+  }
+
+  private async ensureInputDuration(inputPath: string) {
+    if (this.maxInputSeconds <= 0) {
+      return;
+    }
+    
+    const { durationSeconds } = await this.probeMedia(inputPath);
+    if (durationSeconds !== undefined && durationSeconds > this.maxInputSeconds) {
+      throw new ProcessingError(
+        `Input too long (413): ${Math.round(durationSeconds)}s is over the ${this.maxInputSeconds}s limit (VIDEO_MAX_INPUT_SECONDS)`,
+        'network',
+        false
+      );
+    }
+  }
+
+  private async runFFmpeg(args: string[]): Promise<string> {
+    // Ensure FFmpeg is available before running
+    const currentFFmpegPath = await ensureFFmpeg();
+    
+    return new Promise((resolve, reject) => {
+      console.log('Running FFmpeg with path:', currentFFmpegPath);
+      console.log('FFmpeg arguments:', args);
+      
+      // Check if ffmpeg path exists (for static binaries)
+      if (currentFFmpegPath !== 'ffmpeg' && !fs.existsSync(currentFFmpegPath)) {
+        reject(new Error(`FFmpeg binary not found at path: ${currentFFmpegPath}`));
+        return;
+      }
+      
+      const process = spawn(currentFFmpegPath, args);
+      
+      let stderr = '';
+      let stdout = '';
+      
+      process.stdout.on('data', (data) => {
+        stdout += data.toString();
+      });
+      
+      process.stderr.on('data', (data) => {
+        stderr += data.toString();
+      });
+      
+      process.on('close', (code, signal) => {
+        console.log('FFmpeg process finished with code:', code);
+        console.log('FFmpeg stderr:', stderr);
+        console.log('FFmpeg stdout:', stdout);
+        
+        if (code === 0) {
+          resolve(stderr);
+        } else {
+          // A signal (typically SIGKILL from the OOM killer) says more about
+          // the machine at that moment than about the edit, so it may succeed later
+          // ffmpeg's last line usually names what went wrong
+          const lastLine = stderr.trim().split('\n').pop()?.trim();
+          reject(new ProcessingError(
+            `FFmpeg process exited with ${signal ? `signal ${signal}` : `code ${code}`}${lastLine ? `: ${lastLine}` : ''}`,
+            'ffmpeg',
+            signal !== null,
+            `stderr: ${stderr}, stdout: ${stdout}`
+          ));
+        }
+      });
+      
+      process.on('error', (error) => {
+        console.error('FFmpeg spawn error:', error);
+        reject(new ProcessingError(`FFmpeg spawn error: ${error.message}. Path: ${currentFFmpegPath}`, 'ffmpeg', false));
+      });
+    });
+  }
+
+  // ffmpeg prints stream details for a bare `-i` and then exits non-zero since
+  // no output was given, so stderr is parsed whatever the exit code.
+  private async probeMedia(inputPath: string): Promise<MediaInfo> {
+    const currentFFmpegPath = await ensureFFmpeg();
+    
+    const stderr = await new Promise<string>((resolve, reject) => {
+      const process = spawn(currentFFmpegPath, ['-hide_banner', '-i', inputPath]);
+      let output = '';
+      process.stderr.on('data', (data) => {
+        output += data.toString();
+      });
+      process.on('close', () => resolve(output));
+      process.on('error', (error) => {
+        reject(new ProcessingError(`FFmpeg spawn error: ${error.message}. Path: ${currentFFmpegPath}`, 'ffmpeg', false));
+      });
+    });
+    
+    const info: MediaInfo = {};
+    const duration = stderr.match(/Duration: (\d+):(\d+):(\d+(?:\.\d+)?)/);
+    if (duration) {
+      info.durationSeconds = Number(duration[1]) * 3600 + Number(duration[2]) * 60 + Number(duration[3]);
+    }
+    const fps = stderr.match(/Video: .*?, (\d+(?:\.\d+)?) fps/);
+    if (fps) {
+      info.fps = Number(fps[1]);
+    }
+    const videoCodec = stderr.match(/Video: (\w+)/);
+    if (videoCodec) {
+      info.videoCodec = videoCodec[1];
+    }
+    const size = stderr.match(/Video: .*?, (\d+)x(\d+)/);
+    if (size) {
+      info.width = Number(size[1]);
+      info.height = Number(size[2]);
+    }
+    const audio = stderr.match(/Audio: (\w+).*?, (\d+) Hz/);
+    if (audio) {
+      info.audioCodec = audio[1];
+      info.sampleRate = Number(audio[2]);
+    }
+    if (/Video: .*?\b(?:top|bottom) (?:coded )?first/.test(stderr)) {
+      info.interlaced = true;
+    }
+    return info;
+  }
+
+  private generateOutputPath(extension: string = 'mp4'): string {
+    const outputId = generateUUID();
+    return path.join(this.tempDir, `output_${outputId}.${extension}`);
+  }
+
+  private async uploadOutput(filePath: string, originalFilename: string, operation: string, outputName?: string): Promise<string> {
+    const extension = path.extname(filePath).slice(1);
+    // Outputs download under a readable name, after their source unless one
+    // was requested; storage keeps the stored key itself unguessable
+    const stem = sanitizeStem(
+      outputName
+        ? expandOutputName(outputName, { originalFilename, operation, extension })
+        : `${sanitizeStem(originalFilename) || 'processed'}_edited`
+    ) || 'processed';
+    
+    try {
+      return await this.storage.store(filePath, `${stem}.${extension}`, {
+        contentType: contentTypeFor(filePath),
+      });
+    } catch (error: any) {
+      throw new ProcessingError(`Failed to upload processed video: ${error.message}`, 'storage', !(error instanceof StorageQuotaError));
+    }
+  }
+
+  // Reports which edits the installed ffmpeg can run, keyed by operation name,
+  // with what each unavailable one is missing. Presets or modes that can't
+  // run while the rest of the edit can are listed under unavailableVariants.
+  async getOperationAvailability(): Promise<Record<string, { available: boolean; missing: string[]; unavailableVariants?: Record<string, string[]> }>> {
+    const report = missingRequirements(await detectCapabilities());
+    return Object.fromEntries(
+      Object.entries(report).map(([operation, { missing, variants }]) => {
+        const unavailableVariants = Object.entries(variants).filter(([, variantMissing]) => variantMissing.length > 0);
+        return [operation, {
+          available: missing.length === 0,
+          missing,
+          ...(unavailableVariants.length > 0 ? { unavailableVariants: Object.fromEntries(unavailableVariants) } : {}),
+        }];
+      })
+    );
+  }
+
+  private ensureMediaSupported(operation: string, mediaKind: MediaKind) {
+    const supportedMedia = OPERATION_REQUIREMENTS[operation]?.media ?? ['video'];
+    if (!supportedMedia.includes(mediaKind)) {
+      throw new ProcessingError(`${operation} cannot be applied to ${mediaKind} inputs`, 'ffmpeg', false);
+    }
+  }
+
+  // Checks the operation's own needs plus those of the variants (presets,
+  // algorithms, modes) this call uses
+  private async ensureOperationSupported(operation: string, variants: string[] = []): Promise<void> {
+    const report = missingRequirements(await detectCapabilities())[operation];
+    const missing = report?.missing ?? [];
+    if (missing.length > 0) {
+      throw new ProcessingError(
+        `${operation} is unavailable: the installed FFmpeg build is missing ${missing.join(', ')}`,
+        'ffmpeg',
+        false
+      );
+    }
     
-    await this.runFFmpeg(args);
+    for (const variant of variants) {
+      const variantMissing = report?.variants[variant] ?? [];
+      if (variantMissing.length > 0) {
+        throw new ProcessingError(
+          `${operation} (${variant}) is unavailable: the installed FFmpeg build is missing ${variantMissing.join(', ')}`,
+          'ffmpeg',
+          false
+        );
+      }
+    }
+  }
+
+  // Fetches (or resolves, for trusted local files) one source, checks it is
+  // media within the limits, and gives it the extension its contents call for.
+  // Downloaded copies are recorded in tempFiles for removal.
+  private async prepareInput(url: string, attempts: StageAttempts, tempFiles: string[]): Promise<{ path: string; kind: MediaKind; extension: string }> {
+    // Trusted local inputs and our own stored outputs are used in place and
+    // must survive cleanup
+    const localInputPath = this.storage.localPathFor?.(url) ?? this.resolveLocalInput(url);
+    let inputPath = localInputPath
+      ?? await this.downloadVideo(url, attempts);
+    if (!localInputPath) {
+      tempFiles.push(inputPath);
+    }
     
-    const resultUrl = await this.uploadToVercelBlob(outputPath);
+    const sniffed = sniffMediaFile(inputPath);
+    if (!sniffed) {
+      throw new ProcessingError(`${originalFilenameFromUrl(url)} is not a recognized video, audio or image file`, 'network', false);
+    }
+    if (localInputPath) {
+      this.ensureInputSize(fs.statSync(localInputPath).size);
+    }
+    if (sniffed.kind !== 'image') {
+      await this.ensureInputDuration(inputPath);
+    }
     
-    fs.unlinkSync(inputPath);
-    fs.unlinkSync(outputPath);
+    if (!localInputPath && extensionOf(inputPath) !== sniffed.extension) {
+      const renamedPath = inputPath.replace(/\.[^.]*$/, `.${sniffed.extension}`);
+      fs.renameSync(inputPath, renamedPath);
+      tempFiles.push(renamedPath);
+      inputPath = renamedPath;
+    }
     
-    return resultUrl;
+    return { path: inputPath, ...sniffed };
   }
 
-  async adjustBrightness(videoUrl: string, brightness: number): Promise<string> {
-    const inputPath = await this.downloadVideo(videoUrl);
-    const outputPath = this.generateOutputPath();
+  // LUTs are small and the same house grade is applied over and over, so
+  // they are kept in the temp dir, outside any job's cleanup. Files are named
+  // by content hash, with a small index per URL recording the ETag or
+  // Last-Modified they were fetched with: a URL whose LUT changed gets a new
+  // file, and one the origin can't validate is fetched again every time.
+  // The returned path is held against eviction until released with releaseLut.
+  private async cachedLut(url: string): Promise<string> {
+    const { createHash } = require('crypto');
+    const sha256 = (value: string) => createHash('sha256').update(value).digest('hex');
+    const cacheDir = path.join(this.tempDir, 'luts');
+    const indexPath = path.join(cacheDir, `${sha256(url)}.json`);
+    fs.mkdirSync(cacheDir, { recursive: true });
+    
+    let cached: { file: string; etag?: string; lastModified?: string } | null = null;
+    try {
+      cached = JSON.parse(fs.readFileSync(indexPath, 'utf8'));
+      if (!cached || !fs.existsSync(path.join(cacheDir, cached.file))) {
+        cached = null;
+      }
+    } catch {
+      cached = null;
+    }
     
+    const headers: Record<string, string> = {};
+    if (cached?.etag) {
+      headers['If-None-Match'] = cached.etag;
+    } else if (cached?.lastModified) {
+      headers['If-Modified-Since'] = cached.lastModified;
+    }
+    
+    const attempts: StageAttempts = { download: 0, process: 0, upload: 0 };
+    // Its own retries are spent by the time this throws; the process stage
+    // it runs in must not repeat them
+    let fetched: { text: string; etag?: string; lastModified?: string } | null;
+    try {
+      fetched = await this.withRetry('download', attempts, async () => {
+        let response: Response;
+        try {
+          response = await this.httpClient(url, { headers });
+        } catch (error: any) {
+          throw new ProcessingError(`Failed to download LUT: ${error.message}`, 'network', !(error instanceof BlockedUrlError));
+        }
+        if (response.status === 304 && cached) {
+          await response.body?.cancel();
+          return null;
+        }
+        if (!response.ok) {
+          throw new ProcessingError(`Failed to download LUT: HTTP ${response.status}`, 'network', isTransientStatus(response.status));
+        }
+        if (Number(response.headers.get('content-length') ?? 0) > MAX_LUT_BYTES) {
+          throw new ProcessingError('LUT is too large', 'network', false);
+        }
+        if (!response.body) {
+          throw new ProcessingError('Failed to download LUT: empty response body', 'network', true);
+        }
+        
+        // Counted as it arrives, since the length may be missing or wrong
+        const chunks: Buffer[] = [];
+        let received = 0;
+        try {
+          for await (const chunk of Readable.fromWeb(response.body as unknown as NodeReadableStream)) {
+            received += chunk.length;
+            if (received > MAX_LUT_BYTES) {
+              throw new ProcessingError('LUT is too large', 'network', false);
+            }
+            chunks.push(chunk);
+          }
+        } catch (error: any) {
+          if (error instanceof ProcessingError) {
+            throw error;
+          }
+          throw new ProcessingError(`Failed to download LUT: ${error.message}`, 'network', true);
+        }
+        return {
+          text: Buffer.concat(chunks).toString('utf8'),
+          // Weak ETags are fine for If-None-Match
+          etag: response.headers.get('etag') ?? undefined,
+          lastModified: response.headers.get('last-modified') ?? undefined,
+        };
+      });
+    } catch (error) {
+      if (error instanceof ProcessingError) {
+        error.transient = false;
+      }
+      throw error;
+    }
+    
+    if (!fetched && cached) {
+      const lutPath = path.join(cacheDir, cached.file);
+      const now = new Date();
+      fs.utimesSync(lutPath, now, now);
+      this.holdLut(lutPath);
+      return lutPath;
+    }
+    if (!fetched || cubeLutSize(fetched.text) === null) {
+      throw new ProcessingError(`${originalFilenameFromUrl(url)} is not a valid 3D .cube LUT`, 'network', false);
+    }
+    
+    this.evictLuts(cacheDir);
+    
+    // Written under temporary names so a concurrent edit never reads half a file
+    const file = `${sha256(fetched.text)}.cube`;
+    const lutPath = path.join(cacheDir, file);
+    if (fs.existsSync(lutPath)) {
+      const now = new Date();
+      fs.utimesSync(lutPath, now, now);
+    } else {
+      const partialPath = `${lutPath}.${generateUUID()}.part`;
+      fs.writeFileSync(partialPath, fetched.text);
+      fs.renameSync(partialPath, lutPath);
+    }
+    const partialIndexPath = `${indexPath}.${generateUUID()}.part`;
+    fs.writeFileSync(partialIndexPath, JSON.stringify({ file, etag: fetched.etag, lastModified: fetched.lastModified }));
+    fs.renameSync(partialIndexPath, indexPath);
+    this.holdLut(lutPath);
+    return lutPath;
+  }
+
+  // Keeps the most recently used LUTs. Files still being written (.part), held
+  // by an edit in this process, or touched recently (possibly by another
+  // process sharing the temp dir) are left alone; indexes pointing at an
+  // evicted file are treated as misses.
+  private evictLuts(cacheDir: string) {
+    const now = Date.now();
+    const luts = fs.readdirSync(cacheDir)
+      .filter((name) => name.endsWith('.cube'))
+      .map((name) => {
+        const filePath = path.join(cacheDir, name);
+        return { filePath, usedAt: fs.statSync(filePath).mtimeMs };
+      })
+      .sort((a, b) => b.usedAt - a.usedAt);
+    
+    for (const { filePath, usedAt } of luts.slice(LUT_CACHE_ENTRIES - 1)) {
+      if (this.lutsInUse.has(filePath) || now - usedAt < LUT_CACHE_GRACE_MS) {
+        continue;
+      }
+      fs.rmSync(filePath, { force: true });
+    }
+  }
+
+  // Holds are counted, so eviction skips a LUT until every edit using it
+  // has released it
+  private holdLut(lutPath: string) {
+    this.lutsInUse.set(lutPath, (this.lutsInUse.get(lutPath) ?? 0) + 1);
+  }
+
+  private releaseLut(lutPath: string) {
+    const remaining = (this.lutsInUse.get(lutPath) ?? 1) - 1;
+    if (remaining > 0) {
+      this.lutsInUse.set(lutPath, remaining);
+    } else {
+      this.lutsInUse.delete(lutPath);
+    }
+  }
+
+  // Runs the download → ffmpeg → upload sequence shared by every edit, timing
+  // each stage so callers can tell a slow origin from a slow encode.
+  // Edits that combine sources (audio replacement, overlays, concatenation)
+  // pass the others as named extraInputs; buildArgs receives their local
+  // paths under the same names. Output is named after the primary source.
+  // Segmented edits get an outputPath containing %03d and every file ffmpeg
+  // writes from it is uploaded, in order, or zipped into one upload when
+  // options.bundle is set.
+  private async processVideo(
+    operation: string,
+    videoUrl: string,
+    options: EditOptions,
+    buildArgs: (
+      inputPath: string,
+      outputPath: string,
+      scratchPath: (extension: string) => string,
+      inputs: Record<string, string>
+    ) => string[] | Promise<string[]>,
+    outputExtension?: string,
+    extraInputs: Record<string, string> = {},
+    segmented: boolean = false
+  ): Promise<ProcessedVideo> {
+    await this.ensureOperationSupported(operation);
+    
+    // The name is only a hint to fail fast; the downloaded bytes decide below
+    const originalFilename = originalFilenameFromUrl(videoUrl);
+    this.ensureMediaSupported(operation, mediaKindOf(originalFilename));
+    
+    await this.acquireJobSlot();
+    
+    // Everything written for this edit is removed however it ends
+    const tempFiles: string[] = [];
+    
+    try {
+      this.ensureTempSpace();
+      
+      const startedAt = Date.now();
+      
+      const attempts: StageAttempts = { download: 0, process: 0, upload: 0 };
+      
+      const input = await this.prepareInput(videoUrl, attempts, tempFiles);
+      this.ensureMediaSupported(operation, input.kind);
+      const inputPath = input.path;
+      
+      const inputs: Record<string, string> = {};
+      for (const [name, url] of Object.entries(extraInputs)) {
+        inputs[name] = (await this.prepareInput(url, attempts, tempFiles)).path;
+      }
+      const downloadedAt = Date.now();
+      
+      // Side files an edit needs (metadata, lists, LUTs) live next to the input
+      const scratchPath = (extension: string) => {
+        const filePath = path.join(this.tempDir, `scratch_${generateUUID()}.${extension}`);
+        tempFiles.push(filePath);
+        return filePath;
+      };
+      
+      // Images and audio stay in their own format unless the edit converts them
+      let outputPath = this.generateOutputPath(outputExtension ?? (input.kind === 'video' ? 'mp4' : input.extension));
+      if (segmented) {
+        // Segments get a directory of their own so they can be listed afterwards
+        const segmentDir = outputPath.replace(/\.[^.]*$/, '');
+        fs.mkdirSync(segmentDir);
+        tempFiles.push(segmentDir);
+        outputPath = path.join(segmentDir, `part_%03d${path.extname(outputPath)}`);
+      } else {
+        tempFiles.push(outputPath);
+      }
+      await this.withRetry('process', attempts, async () => this.runFFmpeg(await buildArgs(inputPath, outputPath, scratchPath, inputs)));
+      
+      const outputPaths = segmented
+        ? fs.readdirSync(path.dirname(outputPath)).sort().map((name) => path.join(path.dirname(outputPath), name))
+        : [outputPath];
+      // Reading back the result counts as processing, as does bundling
+      const { fps } = await this.probeMedia(outputPaths[0]);
+      const partName = (index: number) =>
+        `${options.outputName ?? `${sanitizeStem(originalFilename) || 'processed'}_part`}-${index + 1}`;
+      const bundlePath = segmented && options.bundle ? scratchPath('zip') : undefined;
+      if (bundlePath) {
+        try {
+          await writeZip(bundlePath, outputPaths.map((filePath, index) => {
+            const extension = path.extname(filePath).slice(1);
+            const stem = sanitizeStem(expandOutputName(partName(index), { originalFilename, operation, extension }));
+            return { name: `${stem}.${extension}`, filePath };
+          }));
+        } catch (error: any) {
+          throw new ProcessingError(`Failed to bundle outputs: ${error.message}`, 'storage', false);
+        }
+      }
+      const processedAt = Date.now();
+      
+      // Upload the processed files and return their URLs; segments are
+      // numbered after the requested name or the source
+      const urls: string[] = [];
+      if (bundlePath) {
+        const bundleName = options.outputName ?? `${sanitizeStem(originalFilename) || 'processed'}_parts`;
+        urls.push(await this.withRetry('upload', attempts, () => this.uploadOutput(bundlePath, originalFilename, operation, bundleName)));
+      } else {
+        for (const [index, filePath] of outputPaths.entries()) {
+          const outputName = segmented ? partName(index) : options.outputName;
+          urls.push(await this.withRetry('upload', attempts, () => this.uploadOutput(filePath, originalFilename, operation, outputName)));
+        }
+      }
+      const uploadedAt = Date.now();
+      
+      return {
+        url: urls[0],
+        ...(segmented && !bundlePath ? { urls } : {}),
+        originalFilename,
+        ...(fps !== undefined ? { fps } : {}),
+        attempts,
+        timings: {
+          downloadMs: downloadedAt - startedAt,
+          processMs: processedAt - downloadedAt,
+          uploadMs: uploadedAt - processedAt,
+          totalMs: uploadedAt - startedAt,
+        },
+      };
+    } finally {
+      // Clean up temp files
+      for (const filePath of tempFiles) {
+        fs.rmSync(filePath, { force: true, recursive: true });
+      }
+      this.releaseJobSlot();
+    }
+  }
+
+  // Stream copy is fast but can only cut on keyframes; accurate mode decodes
+  // from the start point and re-encodes so the cut lands on the exact frame.
+  async trimVideo(
+    videoUrl: string,
+    startTime: TimeInput,
+    endTime: TimeInput,
+    accurate: boolean = false,
+    options: EditOptions = {}
+  ): Promise<ProcessedVideo> {
+    return this.processVideo('trimVideo', videoUrl, options, async (inputPath, outputPath) => {
+      const fps = usesFrames(startTime, endTime) ? (await this.probeMedia(inputPath)).fps : undefined;
+      const start = parseTimeInput(startTime, fps);
+      const end = parseTimeInput(endTime, fps);
+      if (end <= start) {
+        throw new ProcessingError(`Trim end (${end}s) must be after its start (${start}s)`, 'ffmpeg', false);
+      }
+      
+      return [
+        '-i', inputPath,
+        '-ss', start.toString(),
+        '-t', (end - start).toString(),
+        ...(accurate ? [] : ['-c', 'copy']),
+        '-y',
+        outputPath
+      ];
+    });
+  }
+
+  // Several ranges from one download: either one output per range, or the
+  // ranges joined in the given order into a single highlight video (which
+  // always re-encodes).
+  async extractClips(
+    videoUrl: string,
+    ranges: Array<{ start: TimeInput; end: TimeInput }>,
+    join: boolean = false,
+    accurate: boolean = false,
+    options: EditOptions = {}
+  ): Promise<ProcessedVideo> {
+    return this.processVideo('extractClips', videoUrl, options, async (inputPath, outputPath) => {
+      const info = await this.probeMedia(inputPath);
+      const seconds = ranges.map(({ start, end }) => {
+        const from = parseTimeInput(start, info.fps);
+        const to = parseTimeInput(end, info.fps);
+        if (to <= from) {
+          throw new ProcessingError(`Clip end (${to}s) must be after its start (${from}s)`, 'ffmpeg', false);
+        }
+        return { from, to };
+      });
+      
+      if (!join) {
+        // One ffmpeg run writing an output per range
+        return [
+          '-y',
+          '-i', inputPath,
+          ...seconds.flatMap(({ from, to }, index) => [
+            '-map', '0',
+            '-ss', from.toString(),
+            '-t', (to - from).toString(),
+            ...(accurate ? [] : ['-c', 'copy']),
+            outputPath.replace('%03d', (index + 1).toString().padStart(3, '0')),
+          ]),
+        ];
+      }
+      
+      const hasVideo = info.videoCodec !== undefined;
+      const hasAudio = info.audioCodec !== undefined;
+      const graph: string[] = [];
+      const pairs = seconds.map(({ from, to }, index) => {
+        if (hasVideo) graph.push(`[0:v]trim=start=${from}:end=${to},setpts=PTS-STARTPTS[v${index}]`);
+        if (hasAudio) graph.push(`[0:a]atrim=start=${from}:end=${to},asetpts=PTS-STARTPTS[a${index}]`);
+        return `${hasVideo ? `[v${index}]` : ''}${hasAudio ? `[a${index}]` : ''}`;
+      });
+      graph.push(`${pairs.join('')}concat=n=${seconds.length}:v=${hasVideo ? 1 : 0}:a=${hasAudio ? 1 : 0}${hasVideo ? '[v]' : ''}${hasAudio ? '[a]' : ''}`);
+      
+      return [
+        '-i', inputPath,
+        '-filter_complex', graph.join(';'),
+        ...(hasVideo ? ['-map', '[v]'] : []),
+        ...(hasAudio ? ['-map', '[a]'] : []),
+        '-y',
+        outputPath
+      ];
+    }, undefined, {}, !join);
+  }
+
+  async adjustSpeed(videoUrl: string, speed: number, options: EditOptions = {}): Promise<ProcessedVideo> {
+    const videoSpeed = 1 / speed; // FFmpeg uses inverse for video speed
+    const audioSpeed = speed;
+    
+    return this.processVideo('adjustSpeed', videoUrl, options, (inputPath, outputPath) => [
+      '-i', inputPath,
+      '-filter_complex', `[0:v]setpts=${videoSpeed}*PTS[v];[0:a]atempo=${audioSpeed}[a]`,
+      '-map', '[v]',
+      '-map', '[a]',
+      '-y',
+      outputPath
+    ]);
+  }
+
+  // Keyframed speed: holds the first point's speed before it and the last
+  // point's after it, easing linearly between points. Each stretch of
+  // constant speed is trimmed out, retimed and re-joined; sound is
+  // time-stretched to match, or dropped when muteAudio is set.
+  async rampSpeed(videoUrl: string, points: SpeedPoint[], muteAudio: boolean = false, options: EditOptions = {}): Promise<ProcessedVideo> {
+    const sorted = [...points].sort((a, b) => a.time - b.time);
+    
+    return this.processVideo('rampSpeed', videoUrl, options, async (inputPath, outputPath) => {
+      const { durationSeconds, audioCodec } = await this.probeMedia(inputPath);
+      if (durationSeconds === undefined) {
+        throw new ProcessingError('Could not determine the duration needed to ramp speed', 'ffmpeg', false);
+      }
+      
+      const steps: Array<{ start: number; end: number; speed: number }> = [];
+      if (sorted[0].time > 0) {
+        steps.push({ start: 0, end: sorted[0].time, speed: sorted[0].speed });
+      }
+      for (let index = 0; index + 1 < sorted.length; index++) {
+        const from = sorted[index];
+        const to = sorted[index + 1];
+        for (let start = from.time; start < to.time; start += RAMP_STEP_SECONDS) {
+          const end = Math.min(start + RAMP_STEP_SECONDS, to.time);
+          const progress = ((start + end) / 2 - from.time) / (to.time - from.time);
+          steps.push({ start, end, speed: from.speed + (to.speed - from.speed) * progress });
+        }
+      }
+      const last = sorted[sorted.length - 1];
+      steps.push({ start: last.time, end: durationSeconds, speed: last.speed });
+      
+      const withAudio = Boolean(audioCodec) && !muteAudio;
+      const graph: string[] = [];
+      const pairs = steps.filter((step) => step.end > step.start).map((step, index) => {
+        graph.push(`[0:v]trim=start=${step.start}:end=${step.end},setpts=(PTS-STARTPTS)/${step.speed}[v${index}]`);
+        if (withAudio) {
+          graph.push(`[0:a]atrim=start=${step.start}:end=${step.end},asetpts=PTS-STARTPTS,${atempoChain(step.speed)}[a${index}]`);
+        }
+        return withAudio ? `[v${index}][a${index}]` : `[v${index}]`;
+      });
+      graph.push(`${pairs.join('')}concat=n=${pairs.length}:v=1:a=${withAudio ? 1 : 0}[v]${withAudio ? '[a]' : ''}`);
+      
+      return [
+        '-i', inputPath,
+        '-filter_complex', graph.join(';'),
+        '-map', '[v]',
+        ...(withAudio ? ['-map', '[a]'] : ['-an']),
+        '-y',
+        outputPath
+      ];
+    });
+  }
+
+  async adjustBrightness(videoUrl: string, brightness: number, options: EditOptions = {}): Promise<ProcessedVideo> {
     // Convert brightness from -100/100 to 0-2 range for FFmpeg
     const brightnessValue = (brightness + 100) / 100;
     
-    const args = [
+    return this.processVideo('adjustBrightness', videoUrl, options, (inputPath, outputPath) => [
       '-i', inputPath,
       '-vf', `eq=brightness=${brightnessValue - 1}`,
       '-y',
       outputPath
-    ];
-    
-    await this.runFFmpeg(args);
-    
-    const resultUrl = await this.uploadToVercelBlob(outputPath);
-    
-    fs.unlinkSync(inputPath);
-    fs.unlinkSync(outputPath);
-    
-    return resultUrl;
+    ]);
   }
 
   async addText(
@@ -325,11 +1861,9 @@ class VideoProcessor {
     text: string, 
     position: 'top' | 'center' | 'bottom',
     startTime?: number,
-    endTime?: number
-  ): Promise<string> {
-    const inputPath = await this.downloadVideo(videoUrl);
-    const outputPath = this.generateOutputPath();
-    
+    endTime?: number,
+    options: EditOptions = {}
+  ): Promise<ProcessedVideo> {
     let yPosition = 'h-th-10'; // bottom
     if (position === 'top') yPosition = '10';
     if (position === 'center') yPosition = '(h-th)/2';
@@ -340,94 +1874,77 @@ class VideoProcessor {
       filterString += `:enable='between(t,${startTime},${endTime})'`;
     }
     
-    const args = [
+    return this.processVideo('addText', videoUrl, options, (inputPath, outputPath) => [
       '-i', inputPath,
       '-vf', filterString,
       '-y',
       outputPath
-    ];
-    
-    await this.runFFmpeg(args);
-    
-    const resultUrl = await this.uploadToVercelBlob(outputPath);
-    
-    fs.unlinkSync(inputPath);
-    fs.unlinkSync(outputPath);
-    
-    return resultUrl;
+    ]);
   }
 
-  async cropVideo(videoUrl: string, x: number, y: number, width: number, height: number): Promise<string> {
-    const inputPath = await this.downloadVideo(videoUrl);
-    const outputPath = this.generateOutputPath();
-    
-    const args = [
+  async cropVideo(videoUrl: string, x: number, y: number, width: number, height: number, options: EditOptions = {}): Promise<ProcessedVideo> {
+    return this.processVideo('cropVideo', videoUrl, options, (inputPath, outputPath) => [
       '-i', inputPath,
       '-vf', `crop=${width}:${height}:${x}:${y}`,
       '-y',
       outputPath
-    ];
-    
-    await this.runFFmpeg(args);
-    
-    const resultUrl = await this.uploadToVercelBlob(outputPath);
-    
-    fs.unlinkSync(inputPath);
-    fs.unlinkSync(outputPath);
-    
-    return resultUrl;
+    ]);
   }
 
-  async rotateVideo(videoUrl: string, degrees: number): Promise<string> {
-    const inputPath = await this.downloadVideo(videoUrl);
-    const outputPath = this.generateOutputPath();
-    
-    let transposeValue = '1'; // 90 degrees clockwise
-    if (degrees === 180) transposeValue = '2,transpose=2';
-    if (degrees === 270) transposeValue = '2';
-    
-    const args = [
-      '-i', inputPath,
-      '-vf', `transpose=${transposeValue}`,
-      '-y',
-      outputPath
-    ];
-    
-    await this.runFFmpeg(args);
+  // Rotates clockwise by a multiple of 90 degrees and/or mirrors the frame.
+  // With metadataOnly, MP4/MOV video keeps its encoded frames and only the
+  // display matrix changes (players apply it); other inputs are re-encoded.
+  async rotateVideo(
+    videoUrl: string,
+    degrees: number,
+    flip?: 'horizontal' | 'vertical',
+    metadataOnly: boolean = false,
+    options: EditOptions = {}
+  ): Promise<ProcessedVideo> {
+    const quarterTurns = ((Math.round(degrees / 90) % 4) + 4) % 4;
     
-    const resultUrl = await this.uploadToVercelBlob(outputPath);
-    
-    fs.unlinkSync(inputPath);
-    fs.unlinkSync(outputPath);
-    
-    return resultUrl;
+    return this.processVideo('rotateVideo', videoUrl, options, (inputPath, outputPath) => {
+      if (metadataOnly && ['mp4', 'mov'].includes(extensionOf(inputPath))) {
+        // -display_rotation is counter-clockwise and needs ffmpeg 6 or newer
+        return [
+          '-display_rotation:v:0', (-quarterTurns * 90).toString(),
+          ...(flip === 'horizontal' ? ['-display_hflip:v:0'] : []),
+          ...(flip === 'vertical' ? ['-display_vflip:v:0'] : []),
+          '-i', inputPath,
+          '-c', 'copy',
+          '-y',
+          outputPath
+        ];
+      }
+      
+      const filters = [
+        ...(flip === 'horizontal' ? ['hflip'] : []),
+        ...(flip === 'vertical' ? ['vflip'] : []),
+      ];
+      if (quarterTurns === 1) filters.push('transpose=1'); // 90 degrees clockwise
+      if (quarterTurns === 2) filters.push('transpose=2,transpose=2');
+      if (quarterTurns === 3) filters.push('transpose=2');
+      
+      return [
+        '-i', inputPath,
+        ...(filters.length > 0 ? ['-vf', filters.join(',')] : []),
+        '-y',
+        outputPath
+      ];
+    });
   }
 
-  async adjustVolume(videoUrl: string, volume: number): Promise<string> {
-    const inputPath = await this.downloadVideo(videoUrl);
-    const outputPath = this.generateOutputPath();
-    
-    const args = [
+  async adjustVolume(videoUrl: string, volume: number, options: EditOptions = {}): Promise<ProcessedVideo> {
+    return this.processVideo('adjustVolume', videoUrl, options, (inputPath, outputPath) => [
       '-i', inputPath,
       '-af', `volume=${volume}`,
       '-y',
       outputPath
-    ];
-    
-    await this.runFFmpeg(args);
-    
-    const resultUrl = await this.uploadToVercelBlob(outputPath);
-    
-    fs.unlinkSync(inputPath);
-    fs.unlinkSync(outputPath);
-    
-    return resultUrl;
+    ]);
   }
 
-  async applyFilter(videoUrl: string, filter: string, intensity: number = 1): Promise<string> {
-    const inputPath = await this.downloadVideo(videoUrl);
-    const outputPath = this.generateOutputPath();
-    
+  async applyFilter(videoUrl: string, filter: FilterPreset, intensity: number = 1, options: EditOptions = {}): Promise<ProcessedVideo> {
+    await this.ensureOperationSupported('applyFilter', [filter]);
     let filterString = '';
     
     switch (filter) {
@@ -443,26 +1960,872 @@ class VideoProcessor {
       case 'sharpen':
         filterString = `unsharp=5:5:${intensity}:5:5:0`;
         break;
+      case 'cinematic':
+        // Lifted contrast and muted color, framed by a vignette under fine grain
+        filterString = [
+          colorAdjustFilter({ contrast: 1 + 0.15 * intensity, saturation: 1 - 0.2 * intensity }),
+          vignetteFilter(0.5 * intensity),
+          grainFilter(0.3 * intensity),
+        ].join(',');
+        break;
       default:
         throw new Error(`Unknown filter: ${filter}`);
     }
     
-    const args = [
+    return this.processVideo('applyFilter', videoUrl, options, (inputPath, outputPath) => [
       '-i', inputPath,
       '-vf', filterString,
       '-y',
       outputPath
+    ]);
+  }
+
+  // Scales to the given size; pass -2 for one side to keep the aspect ratio
+  // (rounded to the even sizes video encoders need)
+  async resizeMedia(mediaUrl: string, width: number, height: number, options: EditOptions = {}): Promise<ProcessedVideo> {
+    return this.processVideo('resizeMedia', mediaUrl, options, (inputPath, outputPath) => [
+      '-i', inputPath,
+      '-vf', `scale=${width}:${height}`,
+      '-y',
+      outputPath
+    ]);
+  }
+
+  async convertImage(imageUrl: string, format: 'jpg' | 'png' | 'webp', options: EditOptions = {}): Promise<ProcessedVideo> {
+    return this.processVideo('convertImage', imageUrl, options, (inputPath, outputPath) => [
+      '-i', inputPath,
+      '-frames:v', '1',
+      '-y',
+      outputPath
+    ], format);
+  }
+
+  // EBU R128 loudness normalization to the given integrated loudness (LUFS)
+  async normalizeAudio(mediaUrl: string, targetLufs: number = -16, options: EditOptions = {}): Promise<ProcessedVideo> {
+    return this.processVideo('normalizeAudio', mediaUrl, options, (inputPath, outputPath) => [
+      '-i', inputPath,
+      '-af', `loudnorm=I=${targetLufs}:TP=-1.5:LRA=11`,
+      // loudnorm resamples to 192kHz internally, more than most encoders accept
+      '-ar', '48000',
+      '-c:v', 'copy',
+      '-y',
+      outputPath
+    ]);
+  }
+
+  async fadeAudio(mediaUrl: string, fadeIn: number, fadeOut: number, options: EditOptions = {}): Promise<ProcessedVideo> {
+    return this.processVideo('fadeAudio', mediaUrl, options, async (inputPath, outputPath) => {
+      const fades: string[] = [];
+      if (fadeIn > 0) {
+        fades.push(`afade=t=in:st=0:d=${fadeIn}`);
+      }
+      if (fadeOut > 0) {
+        // The fade-out has to start relative to the end, so it needs the duration
+        const { durationSeconds } = await this.probeMedia(inputPath);
+        if (durationSeconds === undefined) {
+          throw new ProcessingError('Could not determine the duration needed for a fade-out', 'ffmpeg', false);
+        }
+        fades.push(`afade=t=out:st=${Math.max(0, durationSeconds - fadeOut)}:d=${fadeOut}`);
+      }
+      
+      return [
+        '-i', inputPath,
+        '-af', fades.join(',') || 'anull',
+        '-c:v', 'copy',
+        '-y',
+        outputPath
+      ];
+    });
+  }
+
+  // Extracts or converts the audio track; works on video sources too
+  async transcodeAudio(mediaUrl: string, format: 'mp3' | 'wav' | 'm4a', options: EditOptions = {}): Promise<ProcessedVideo> {
+    return this.processVideo('transcodeAudio', mediaUrl, options, (inputPath, outputPath) => [
+      '-i', inputPath,
+      '-vn',
+      '-y',
+      outputPath
+    ], format);
+  }
+
+  async renderWaveform(mediaUrl: string, width: number = 1280, height: number = 240, options: EditOptions = {}): Promise<ProcessedVideo> {
+    return this.processVideo('renderWaveform', mediaUrl, options, (inputPath, outputPath) => [
+      '-i', inputPath,
+      '-filter_complex', `showwavespic=s=${width}x${height}`,
+      '-frames:v', '1',
+      '-y',
+      outputPath
+    ], 'png');
+  }
+
+  // Writes chapter markers via an ffmetadata file; each chapter runs until the
+  // next one starts, the last until the end of the source.
+  async setChapters(mediaUrl: string, chapters: Chapter[], options: EditOptions = {}): Promise<ProcessedVideo> {
+    return this.processVideo('setChapters', mediaUrl, options, async (inputPath, outputPath, scratchPath) => {
+      const sorted = [...chapters].sort((a, b) => a.start - b.start);
+      const { durationSeconds } = await this.probeMedia(inputPath);
+      if (durationSeconds === undefined) {
+        throw new ProcessingError('Could not determine the duration needed to close the last chapter', 'ffmpeg', false);
+      }
+      
+      const lines = [';FFMETADATA1'];
+      sorted.forEach((chapter, index) => {
+        const end = index + 1 < sorted.length ? sorted[index + 1].start : durationSeconds;
+        lines.push(
+          '[CHAPTER]',
+          'TIMEBASE=1/1000',
+          `START=${Math.round(chapter.start * 1000)}`,
+          `END=${Math.round(end * 1000)}`,
+          `title=${escapeMetadata(chapter.title)}`
+        );
+      });
+      
+      const metadataPath = scratchPath('txt');
+      fs.writeFileSync(metadataPath, lines.join('\n') + '\n');
+      
+      return [
+        '-i', inputPath,
+        '-i', metadataPath,
+        '-map', '0',
+        '-map_metadata', '1',
+        '-map_chapters', '1',
+        '-c', 'copy',
+        '-y',
+        outputPath
+      ];
+    });
+  }
+
+  // Swaps the soundtrack for another file's audio. The picture is copied
+  // untouched; the result ends with whichever of the two is shorter.
+  async replaceAudio(videoUrl: string, audioUrl: string, options: EditOptions = {}): Promise<ProcessedVideo> {
+    return this.processVideo('replaceAudio', videoUrl, options, (inputPath, outputPath, _scratchPath, inputs) => [
+      '-i', inputPath,
+      '-i', inputs.audio,
+      '-map', '0:v:0',
+      '-map', '1:a:0',
+      '-c:v', 'copy',
+      '-c:a', 'aac',
+      '-shortest',
+      '-y',
+      outputPath
+    ], undefined, { audio: audioUrl });
+  }
+
+  // Shows each image for secondsPerImage in order, letterboxed onto a
+  // width x height frame so mixed sizes and orientations line up.
+  async createSlideshow(
+    imageUrls: string[],
+    secondsPerImage: number = 3,
+    width: number = 1920,
+    height: number = 1080,
+    options: EditOptions = {}
+  ): Promise<ProcessedVideo> {
+    const [firstImageUrl, ...otherImageUrls] = imageUrls;
+    const extraInputs = Object.fromEntries(otherImageUrls.map((url, index) => [`image${index + 1}`, url]));
+    
+    return this.processVideo('createSlideshow', firstImageUrl, options, (inputPath, outputPath, _scratchPath, inputs) => {
+      const imagePaths = [inputPath, ...otherImageUrls.map((_url, index) => inputs[`image${index + 1}`])];
+      const frames = imagePaths.map((_imagePath, index) =>
+        `[${index}:v]scale=${width}:${height}:force_original_aspect_ratio=decrease,` +
+        `pad=${width}:${height}:(ow-iw)/2:(oh-ih)/2,setsar=1,fps=30,format=yuv420p[v${index}]`
+      );
+      const labels = imagePaths.map((_imagePath, index) => `[v${index}]`).join('');
+      
+      return [
+        ...imagePaths.flatMap((imagePath) => ['-loop', '1', '-t', secondsPerImage.toString(), '-i', imagePath]),
+        '-filter_complex', `${frames.join(';')};${labels}concat=n=${imagePaths.length}:v=1:a=0[out]`,
+        '-map', '[out]',
+        '-y',
+        outputPath
+      ];
+    }, 'mp4', extraInputs);
+  }
+
+  // Fits the whole frame into a new aspect ratio and fills the bars with a
+  // blurred, enlarged copy of itself (the usual vertical-video treatment)
+  async reframe(videoUrl: string, aspectRatio: keyof typeof REFRAME_CANVASES, options: EditOptions = {}): Promise<ProcessedVideo> {
+    const [width, height] = REFRAME_CANVASES[aspectRatio];
+    
+    return this.processVideo('reframe', videoUrl, options, (inputPath, outputPath) => [
+      '-i', inputPath,
+      '-filter_complex',
+      `[0:v]split=2[bg][fg];` +
+      `[bg]scale=${width}:${height}:force_original_aspect_ratio=increase,crop=${width}:${height},gblur=sigma=30[blurred];` +
+      `[fg]scale=${width}:${height}:force_original_aspect_ratio=decrease:force_divisible_by=2[front];` +
+      `[blurred][front]overlay=(W-w)/2:(H-h)/2,setsar=1[v]`,
+      '-map', '[v]',
+      '-map', '0:a?',
+      '-c:a', 'copy',
+      '-y',
+      outputPath
+    ]);
+  }
+
+  // Overlays a still logo (PNG with transparency works best)
+  async addWatermark(videoUrl: string, logoUrl: string, placement: OverlayPlacement = {}, options: EditOptions = {}): Promise<ProcessedVideo> {
+    return this.processVideo('addWatermark', videoUrl, options, (inputPath, outputPath, _scratchPath, inputs) => [
+      '-i', inputPath,
+      '-i', inputs.logo,
+      '-filter_complex', overlayGraph(placement),
+      '-map', '[v]',
+      '-map', '0:a?',
+      '-c:a', 'copy',
+      '-y',
+      outputPath
+    ], undefined, { logo: logoUrl });
+  }
+
+  // Plays an animated asset (GIF, APNG, or WebM with alpha) over the video
+  // from startTime, once or looped until the video ends
+  async addAnimatedOverlay(
+    videoUrl: string,
+    overlayUrl: string,
+    startTime: number = 0,
+    loop: boolean = false,
+    placement: Omit<OverlayPlacement, 'startTime'> = {},
+    options: EditOptions = {}
+  ): Promise<ProcessedVideo> {
+    return this.processVideo('addAnimatedOverlay', videoUrl, options, async (inputPath, outputPath, _scratchPath, inputs) => {
+      // ffmpeg's native VP8/VP9 decoders drop the alpha channel; libvpx keeps it
+      const { videoCodec } = await this.probeMedia(inputs.overlay);
+      if (videoCodec === 'vp8' || videoCodec === 'vp9') {
+        await this.ensureOperationSupported('addAnimatedOverlay', [videoCodec]);
+      }
+      const decoder = videoCodec === 'vp9' ? ['-c:v', 'libvpx-vp9'] : videoCodec === 'vp8' ? ['-c:v', 'libvpx'] : [];
+      
+      return [
+        '-i', inputPath,
+        '-itsoffset', startTime.toString(),
+        ...(loop ? ['-stream_loop', '-1'] : []),
+        ...decoder,
+        '-i', inputs.overlay,
+        // A looped overlay never ends, so stop with the video; a single play
+        // disappears when done instead of freezing on its last frame
+        '-filter_complex', overlayGraph(placement, loop ? ':shortest=1' : ':eof_action=pass'),
+        '-map', '[v]',
+        '-map', '0:a?',
+        '-c:a', 'copy',
+        '-y',
+        outputPath
+      ];
+    }, undefined, { overlay: overlayUrl });
+  }
+
+  // Puts 2-4 videos in one frame: side by side, stacked, or a 2x2 grid.
+  // Each is letterboxed into an equal cell; the result ends with the
+  // shortest input. Sound comes from one input or a mix of all of them
+  // (mixing needs every input to have audio).
+  async composeGrid(
+    videoUrls: string[],
+    layout: 'side-by-side' | 'stacked' | 'grid',
+    audio: 'mix' | number = 0,
+    cellWidth: number = 960,
+    cellHeight: number = 540,
+    options: EditOptions = {}
+  ): Promise<ProcessedVideo> {
+    if (audio !== 'mix' && (audio < 0 || audio >= videoUrls.length)) {
+      throw new ProcessingError(
+        `Audio source ${audio} doesn't exist: give the index of one of the ${videoUrls.length} videos (0-${videoUrls.length - 1}) or 'mix'`,
+        'ffmpeg',
+        false
+      );
+    }
+    const [firstVideoUrl, ...otherVideoUrls] = videoUrls;
+    const extraInputs = Object.fromEntries(otherVideoUrls.map((url, index) => [`video${index + 1}`, url]));
+    
+    return this.processVideo('composeGrid', firstVideoUrl, options, (inputPath, outputPath, _scratchPath, inputs) => {
+      const videoPaths = [inputPath, ...otherVideoUrls.map((_url, index) => inputs[`video${index + 1}`])];
+      const count = videoPaths.length;
+      
+      const cells = videoPaths.map((_videoPath, index) =>
+        `[${index}:v]scale=${cellWidth}:${cellHeight}:force_original_aspect_ratio=decrease,` +
+        `pad=${cellWidth}:${cellHeight}:(ow-iw)/2:(oh-ih)/2,setsar=1,fps=30[c${index}]`
+      );
+      const labels = videoPaths.map((_videoPath, index) => `[c${index}]`).join('');
+      
+      let stack = `${labels}hstack=inputs=${count}:shortest=1[v]`;
+      if (layout === 'stacked') {
+        stack = `${labels}vstack=inputs=${count}:shortest=1[v]`;
+      } else if (layout === 'grid') {
+        // Row-major 2x2; a missing fourth cell is left black
+        const positions = ['0_0', `${cellWidth}_0`, `0_${cellHeight}`, `${cellWidth}_${cellHeight}`];
+        stack = `${labels}xstack=inputs=${count}:layout=${positions.slice(0, count).join('|')}:fill=black:shortest=1[v]`;
+      }
+      
+      const graph = [...cells, stack];
+      if (audio === 'mix') {
+        graph.push(`${videoPaths.map((_videoPath, index) => `[${index}:a]`).join('')}amix=inputs=${count}:duration=shortest[a]`);
+      }
+      
+      return [
+        ...videoPaths.flatMap((videoPath) => ['-i', videoPath]),
+        '-filter_complex', graph.join(';'),
+        '-map', '[v]',
+        '-map', audio === 'mix' ? '[a]' : `${audio}:a?`,
+        '-shortest',
+        '-y',
+        outputPath
+      ];
+    }, 'mp4', extraInputs);
+  }
+
+  // Joins clips end to end. Clips encoded alike (same codecs, frame size,
+  // frame rate and sample rate) are stream-copied through the concat demuxer;
+  // anything else is re-encoded, fitted to the first clip's frame size.
+  // Transitions (one per junction, or one for all) always re-encode.
+  async concatVideos(videoUrls: string[], transitions: Transition[] = [], options: EditOptions = {}): Promise<ProcessedVideo> {
+    await this.ensureOperationSupported('concatVideos', transitions.length > 0 ? ['transitions'] : []);
+    const [firstVideoUrl, ...otherVideoUrls] = videoUrls;
+    const extraInputs = Object.fromEntries(otherVideoUrls.map((url, index) => [`video${index + 1}`, url]));
+    
+    return this.processVideo('concatVideos', firstVideoUrl, options, async (inputPath, outputPath, scratchPath, inputs) => {
+      const videoPaths = [inputPath, ...otherVideoUrls.map((_url, index) => inputs[`video${index + 1}`])];
+      const infos = await Promise.all(videoPaths.map((videoPath) => this.probeMedia(videoPath)));
+      
+      const signature = (info: MediaInfo) =>
+        [info.videoCodec, info.width, info.height, info.fps, info.audioCodec, info.sampleRate].join('/');
+      if (transitions.length === 0 && infos.every((info) => signature(info) === signature(infos[0]))) {
+        const listPath = scratchPath('txt');
+        fs.writeFileSync(listPath, videoPaths.map(concatListEntry).join('\n') + '\n');
+        return [
+          '-f', 'concat',
+          '-safe', '0',
+          '-i', listPath,
+          '-c', 'copy',
+          '-y',
+          outputPath
+        ];
+      }
+      
+      const junctions = videoPaths.slice(1).map((_videoPath, index) => transitions[index] ?? transitions[transitions.length - 1]);
+      return this.normalizedConcatArgs(videoPaths, infos, outputPath, transitions.length > 0 ? junctions : []);
+    }, 'mp4', extraInputs);
+  }
+
+  // Cuts a video into consecutive parts: every `every` seconds, at explicit
+  // timestamps, or into `parts` equal pieces. Stream copy is fast but each
+  // cut moves to the next keyframe; otherwise keyframes are forced at the
+  // cut points so parts start exactly where asked.
+  async splitVideo(
+    videoUrl: string,
+    split: { every: number } | { at: number[] } | { parts: number },
+    copy: boolean = false,
+    options: EditOptions = {}
+  ): Promise<ProcessedVideo> {
+    return this.processVideo('splitVideo', videoUrl, options, async (inputPath, outputPath) => {
+      let cutTimes: number[] = [];
+      if ('at' in split) {
+        cutTimes = [...split.at].sort((a, b) => a - b);
+      } else if ('parts' in split) {
+        const { durationSeconds } = await this.probeMedia(inputPath);
+        if (durationSeconds === undefined) {
+          throw new ProcessingError('Could not determine the duration needed to split into equal parts', 'ffmpeg', false);
+        }
+        cutTimes = Array.from({ length: split.parts - 1 }, (_value, index) => (durationSeconds * (index + 1)) / split.parts);
+      }
+      
+      const segmentArgs = 'every' in split
+        ? ['-segment_time', split.every.toString()]
+        : ['-segment_times', cutTimes.map((time) => time.toFixed(3)).join(',')];
+      const keyframeArgs = 'every' in split
+        ? ['-force_key_frames', `expr:gte(t,n_forced*${split.every})`]
+        : ['-force_key_frames', cutTimes.map((time) => time.toFixed(3)).join(',')];
+      
+      return [
+        '-i', inputPath,
+        '-map', '0',
+        ...(copy ? ['-c', 'copy'] : keyframeArgs),
+        '-f', 'segment',
+        ...segmentArgs,
+        '-reset_timestamps', '1',
+        '-y',
+        outputPath
+      ];
+    }, undefined, {}, true);
+  }
+
+  // Re-encoding concat: every clip is scaled and padded to the first clip's
+  // frame, given a common frame rate and sample format, and clips without
+  // sound get silence so the audio stays in step with the picture.
+  private normalizedConcatArgs(videoPaths: string[], infos: MediaInfo[], outputPath: string, transitions: Transition[] = []): string[] {
+    const width = infos[0].width ?? 1920;
+    const height = infos[0].height ?? 1080;
+    const fps = infos[0].fps ?? 30;
+    
+    const silentInputs: string[] = [];
+    const graph: string[] = [];
+    videoPaths.forEach((_videoPath, index) => {
+      graph.push(
+        `[${index}:v]scale=${width}:${height}:force_original_aspect_ratio=decrease,` +
+        `pad=${width}:${height}:(ow-iw)/2:(oh-ih)/2,setsar=1,fps=${fps},format=yuv420p[v${index}]`
+      );
+      
+      let audioLabel = `${index}:a`;
+      if (!infos[index].audioCodec) {
+        audioLabel = `${videoPaths.length + silentInputs.length}:a`;
+        silentInputs.push('-f', 'lavfi', '-t', (infos[index].durationSeconds ?? 0).toString(), '-i', 'anullsrc=r=48000:cl=stereo');
+      }
+      graph.push(`[${audioLabel}]aresample=48000,aformat=sample_fmts=fltp:channel_layouts=stereo[a${index}]`);
+    });
+    
+    if (transitions.length === 0) {
+      const pairs = videoPaths.map((_videoPath, index) => `[v${index}][a${index}]`).join('');
+      graph.push(`${pairs}concat=n=${videoPaths.length}:v=1:a=1[v][a]`);
+    } else {
+      // Each transition starts `duration` before the joined-so-far video ends
+      let joinedSeconds = infos[0].durationSeconds ?? 0;
+      let video = '[v0]';
+      let audio = '[a0]';
+      transitions.forEach((transition, index) => {
+        const next = index + 1;
+        const isLast = next === videoPaths.length - 1;
+        const offset = Math.max(joinedSeconds - transition.duration, 0);
+        const videoOut = isLast ? '[v]' : `[vx${next}]`;
+        const audioOut = isLast ? '[a]' : `[ax${next}]`;
+        graph.push(
+          `${video}[v${next}]xfade=transition=${TRANSITION_STYLES[transition.style]}:duration=${transition.duration}:offset=${offset}${videoOut}`,
+          `${audio}[a${next}]acrossfade=d=${transition.duration}${audioOut}`
+        );
+        joinedSeconds = offset + (infos[next].durationSeconds ?? 0);
+        video = videoOut;
+        audio = audioOut;
+      });
+    }
+    
+    return [
+      ...videoPaths.flatMap((videoPath) => ['-i', videoPath]),
+      ...silentInputs,
+      '-filter_complex', graph.join(';'),
+      '-map', '[v]',
+      '-map', '[a]',
+      '-y',
+      outputPath
     ];
+  }
+
+  // The reverse filters hold the whole stream in memory, so longer inputs
+  // are reversed a chunk at a time and the chunks joined last to first
+  async reverseVideo(videoUrl: string, includeAudio: boolean = true, options: EditOptions = {}): Promise<ProcessedVideo> {
+    return this.processVideo('reverseVideo', videoUrl, options, async (inputPath, outputPath, scratchPath) => {
+      const reverseArgs = (input: string[], output: string) => [
+        ...input,
+        '-vf', 'reverse',
+        ...(includeAudio ? ['-af', 'areverse'] : ['-an']),
+        '-y',
+        output
+      ];
+      
+      const { durationSeconds = 0 } = await this.probeMedia(inputPath);
+      if (durationSeconds <= REVERSE_CHUNK_SECONDS) {
+        return reverseArgs(['-i', inputPath], outputPath);
+      }
+      
+      const chunkPaths: string[] = [];
+      for (let start = 0; start < durationSeconds; start += REVERSE_CHUNK_SECONDS) {
+        const chunkPath = scratchPath(path.extname(outputPath).slice(1));
+        await this.runFFmpeg(reverseArgs(['-ss', start.toString(), '-t', REVERSE_CHUNK_SECONDS.toString(), '-i', inputPath], chunkPath));
+        chunkPaths.push(chunkPath);
+      }
+      
+      const listPath = scratchPath('txt');
+      fs.writeFileSync(listPath, chunkPaths.reverse().map(concatListEntry).join('\n') + '\n');
+      return [
+        '-f', 'concat',
+        '-safe', '0',
+        '-i', listPath,
+        '-c', 'copy',
+        '-y',
+        outputPath
+      ];
+    });
+  }
+
+  // Plays a short range forwards then backwards, `repetitions` times over.
+  // Silent, like the social-app effect it imitates.
+  async createBoomerang(
+    videoUrl: string,
+    startTime: number,
+    endTime: number,
+    repetitions: number = 3,
+    options: EditOptions = {}
+  ): Promise<ProcessedVideo> {
+    return this.processVideo('createBoomerang', videoUrl, options, async (inputPath, outputPath) => {
+      const length = endTime - startTime;
+      if (length <= 0 || length > REVERSE_CHUNK_SECONDS) {
+        throw new ProcessingError(`Boomerang range must be between 0 and ${REVERSE_CHUNK_SECONDS} seconds long`, 'ffmpeg', false);
+      }
+      
+      // loop repeats a buffer of frames, so it needs the cycle's frame count
+      const { fps = 30 } = await this.probeMedia(inputPath);
+      const cycleFrames = Math.ceil(2 * length * fps);
+      
+      return [
+        '-i', inputPath,
+        '-filter_complex',
+        `[0:v]trim=start=${startTime}:end=${endTime},setpts=PTS-STARTPTS,split[forward][back];` +
+        `[back]reverse[reversed];` +
+        `[forward][reversed]concat=n=2:v=1:a=0,loop=loop=${repetitions - 1}:size=${cycleFrames}:start=0,setpts=N/FRAME_RATE/TB[v]`,
+        '-map', '[v]',
+        '-an',
+        '-y',
+        outputPath
+      ];
+    });
+  }
+
+  // Holds the frame at `atTime` for `holdSeconds`, then carries on: the
+  // video is split there, the first part's last frame is cloned with tpad,
+  // and the rest re-joined. Sound pauses for the hold as well.
+  async freezeFrame(videoUrl: string, atTime: number, holdSeconds: number, options: EditOptions = {}): Promise<ProcessedVideo> {
+    return this.processVideo('freezeFrame', videoUrl, options, async (inputPath, outputPath) => {
+      const { audioCodec } = await this.probeMedia(inputPath);
+      const graph = [
+        `[0:v]trim=end=${atTime},setpts=PTS-STARTPTS,tpad=stop_mode=clone:stop_duration=${holdSeconds}[before]`,
+        `[0:v]trim=start=${atTime},setpts=PTS-STARTPTS[after]`,
+        `[before][after]concat=n=2:v=1:a=0[v]`,
+      ];
+      if (audioCodec) {
+        // Silence the length of the hold is inserted by delaying the second half
+        graph.push(
+          `[0:a]atrim=end=${atTime},asetpts=PTS-STARTPTS[abefore]`,
+          `[0:a]atrim=start=${atTime},asetpts=PTS-STARTPTS,adelay=${Math.round(holdSeconds * 1000)}:all=1[aafter]`,
+          `[abefore][aafter]concat=n=2:v=0:a=1[a]`
+        );
+      }
+      
+      return [
+        '-i', inputPath,
+        '-filter_complex', graph.join(';'),
+        '-map', '[v]',
+        ...(audioCodec ? ['-map', '[a]'] : []),
+        '-y',
+        outputPath
+      ];
+    });
+  }
+
+  // Slows the video down and fills the gaps with interpolated frames, so
+  // 0.25x plays at the source frame rate instead of stuttering through
+  // repeated frames. Sound is stretched to match unless muteAudio is set.
+  async slowMotion(videoUrl: string, speed: number, quality: InterpolationQuality = 'balanced', muteAudio: boolean = false, options: EditOptions = {}): Promise<ProcessedVideo> {
+    return this.processVideo('slowMotion', videoUrl, options, async (inputPath, outputPath) => {
+      const { fps = 30, audioCodec } = await this.probeMedia(inputPath);
+      const withAudio = Boolean(audioCodec) && !muteAudio;
+      
+      return [
+        '-i', inputPath,
+        '-filter:v', `minterpolate=fps=${fps / speed}:${INTERPOLATION_QUALITIES[quality]},setpts=PTS/${speed}`,
+        ...(withAudio ? ['-filter:a', atempoChain(speed)] : ['-an']),
+        '-r', String(fps),
+        '-y',
+        outputPath
+      ];
+    });
+  }
+
+  // Converts to a new frame rate at the same playback speed. 'drop' drops or
+  // repeats frames, which is quick and right for most conversions; 'interpolate'
+  // synthesizes the new frames from motion, smoother when raising the rate.
+  async changeFps(videoUrl: string, fps: number, method: 'drop' | 'interpolate' = 'drop', options: EditOptions = {}): Promise<ProcessedVideo> {
+    await this.ensureOperationSupported('changeFps', [method]);
+    const filter = method === 'interpolate'
+      ? `minterpolate=fps=${fps}:${INTERPOLATION_QUALITIES.balanced}`
+      : `fps=${fps}`;
+    
+    return this.processVideo('changeFps', videoUrl, options, (inputPath, outputPath) => [
+      '-i', inputPath,
+      '-filter:v', filter,
+      '-c:a', 'copy',
+      '-y',
+      outputPath
+    ]);
+  }
+
+  // Removes combing from interlaced footage. Sources that declare a field
+  // order have every frame deinterlaced; otherwise only frames flagged as
+  // interlaced are touched (unless force is set), so progressive video
+  // passes through unchanged. bwdif is sharper, yadif faster.
+  async deinterlace(videoUrl: string, algorithm: 'yadif' | 'bwdif' = 'bwdif', force: boolean = false, options: EditOptions = {}): Promise<ProcessedVideo> {
+    await this.ensureOperationSupported('deinterlace', [algorithm]);
+    return this.processVideo('deinterlace', videoUrl, options, async (inputPath, outputPath) => {
+      const { interlaced } = await this.probeMedia(inputPath);
+      const frames = interlaced || force ? 'all' : 'interlaced';
+      
+      return [
+        '-i', inputPath,
+        '-filter:v', `${algorithm}=mode=send_frame:deint=${frames}`,
+        '-c:a', 'copy',
+        '-y',
+        outputPath
+      ];
+    });
+  }
+
+  // hqdn3d is quick enough for long clips; nlmeans keeps more detail in
+  // grainy low-light footage but is many times slower
+  async denoise(videoUrl: string, strength: DenoiseStrength = 'medium', algorithm: 'hqdn3d' | 'nlmeans' = 'hqdn3d', options: EditOptions = {}): Promise<ProcessedVideo> {
+    await this.ensureOperationSupported('denoise', [algorithm]);
+    return this.processVideo('denoise', videoUrl, options, (inputPath, outputPath) => [
+      '-i', inputPath,
+      '-filter:v', `${algorithm}=${DENOISE_STRENGTHS[strength][algorithm]}`,
+      '-c:a', 'copy',
+      '-y',
+      outputPath
+    ]);
+  }
+
+  // unsharp works in pixels, so the radius is given for 1080p and scaled to
+  // the input: the same setting then looks alike on 4K and 480p. Small
+  // inputs also have the amount capped, as strong sharpening there mostly
+  // adds halos and compression noise.
+  async sharpen(videoUrl: string, amount: number = 1, radius: number = 2, options: EditOptions = {}): Promise<ProcessedVideo> {
+    return this.processVideo('sharpen', videoUrl, options, async (inputPath, outputPath) => {
+      const { width, height } = await this.probeMedia(inputPath);
+      const shortSide = Math.min(width ?? 1080, height ?? 1080);
+      const scale = shortSide / 1080;
+      
+      // unsharp takes odd matrix sizes from 3 to 23
+      const matrix = Math.min(Math.max(2 * Math.round(radius * scale) + 1, 3), 23);
+      const appliedAmount = shortSide < 720 ? Math.min(amount, 1) : amount;
+      
+      return [
+        '-i', inputPath,
+        '-filter:v', `unsharp=${matrix}:${matrix}:${appliedAmount}:${matrix}:${matrix}:0`,
+        '-c:a', 'copy',
+        '-y',
+        outputPath
+      ];
+    });
+  }
+
+  // Two passes: vidstabdetect records the camera motion to a transforms file,
+  // then vidstabtransform smooths it out, zooming just enough to hide the
+  // moving edges. shakiness (1-10) is how shaky the source is; smoothing is
+  // the number of frames either side averaged into the camera path.
+  async stabilize(videoUrl: string, shakiness: number = 5, smoothing: number = 10, options: EditOptions = {}): Promise<ProcessedVideo> {
+    return this.processVideo('stabilize', videoUrl, options, async (inputPath, outputPath, scratchPath) => {
+      const transformsPath = scratchPath('trf');
+      await this.runFFmpeg([
+        '-i', inputPath,
+        '-vf', `vidstabdetect=shakiness=${shakiness}:accuracy=15:result=${escapeFilterPath(transformsPath)}`,
+        '-f', 'null',
+        '-'
+      ]);
+      
+      return [
+        '-i', inputPath,
+        // Resampling softens the picture slightly; a light unsharp restores it
+        '-vf', `vidstabtransform=input=${escapeFilterPath(transformsPath)}:smoothing=${smoothing}:optzoom=1,unsharp=5:5:0.8:3:3:0.4`,
+        '-c:a', 'copy',
+        '-y',
+        outputPath
+      ];
+    });
+  }
+
+  // Darkens the corners to draw the eye to the middle of the frame
+  async addVignette(videoUrl: string, intensity: number = 0.5, options: EditOptions = {}): Promise<ProcessedVideo> {
+    return this.processVideo('addVignette', videoUrl, options, (inputPath, outputPath) => [
+      '-i', inputPath,
+      '-vf', vignetteFilter(intensity),
+      '-c:a', 'copy',
+      '-y',
+      outputPath
+    ]);
+  }
+
+  async addGrain(videoUrl: string, intensity: number = 0.3, options: EditOptions = {}): Promise<ProcessedVideo> {
+    return this.processVideo('addGrain', videoUrl, options, (inputPath, outputPath) => [
+      '-i', inputPath,
+      '-vf', grainFilter(intensity),
+      '-c:a', 'copy',
+      '-y',
+      outputPath
+    ]);
+  }
+
+  // Blurs or pixelates a rectangle, e.g. to hide a license plate or a face,
+  // for the whole video or only between startTime and endTime. The region
+  // is cut out, obscured and laid back over the frame at the same spot.
+  async obscureRegion(
+    videoUrl: string,
+    region: Region,
+    style: 'blur' | 'pixelate' = 'blur',
+    startTime?: number,
+    endTime?: number,
+    options: EditOptions = {}
+  ): Promise<ProcessedVideo> {
+    const { x, y, width, height } = region;
+    const obscure = style === 'pixelate'
+      // About 12 blocks across the short side, however big the region
+      ? `scale=iw/${Math.max(1, Math.round(Math.min(width, height) / 12))}:-1,scale=${width}:${height}:flags=neighbor`
+      // boxblur allows a radius up to half the chroma plane, a quarter of the region
+      : `boxblur=luma_radius=${Math.max(1, Math.floor(Math.min(width, height) / 8))}:luma_power=3`;
+    const enable = startTime !== undefined || endTime !== undefined
+      ? `:enable='between(t,${startTime ?? 0},${endTime ?? 1e9})'`
+      : '';
     
-    await this.runFFmpeg(args);
+    return this.processVideo('obscureRegion', videoUrl, options, (inputPath, outputPath) => [
+      '-i', inputPath,
+      '-filter_complex', [
+        '[0:v]split[base][copy]',
+        `[copy]crop=${width}:${height}:${x}:${y},${obscure}[hidden]`,
+        `[base][hidden]overlay=${x}:${y}${enable}[v]`,
+      ].join(';'),
+      '-map', '[v]',
+      '-map', '0:a?',
+      '-c:a', 'copy',
+      '-y',
+      outputPath
+    ]);
+  }
+
+  // Keys a solid backdrop (green screen by default) out of the video and
+  // puts the subject over a background image or video, scaled to the
+  // frame. Backgrounds shorter than the video loop. similarity widens the
+  // range of shades removed; blend softens the edge of the key.
+  async chromaKey(
+    videoUrl: string,
+    backgroundUrl: string,
+    color: string = '0x00FF00',
+    similarity: number = 0.3,
+    blend: number = 0.1,
+    options: EditOptions = {}
+  ): Promise<ProcessedVideo> {
+    return this.processVideo('chromaKey', videoUrl, options, (inputPath, outputPath, _scratchPath, inputs) => {
+      const stillBackground = mediaKindOf(inputs.background) === 'image' && extensionOf(inputs.background) !== 'gif';
+      
+      return [
+        '-i', inputPath,
+        ...(stillBackground ? ['-loop', '1'] : ['-stream_loop', '-1']),
+        '-i', inputs.background,
+        '-filter_complex', [
+          '[1:v][0:v]scale2ref[bg][fg]',
+          `[fg]chromakey=color=${color}:similarity=${similarity}:blend=${blend}[keyed]`,
+          '[bg][keyed]overlay=shortest=1,format=yuv420p[v]',
+        ].join(';'),
+        '-map', '[v]',
+        '-map', '0:a?',
+        '-c:a', 'copy',
+        '-y',
+        outputPath
+      ];
+    }, undefined, { background: backgroundUrl });
+  }
+
+  // Grades with a .cube 3D LUT, e.g. a team's house look uploaded through
+  // /api/files/lut. Tetrahedral interpolation is what grading tools use.
+  async applyLut(videoUrl: string, lutUrl: string, options: EditOptions = {}): Promise<ProcessedVideo> {
+    const heldLuts: string[] = [];
+    try {
+      return await this.processVideo('applyLut', videoUrl, options, async (inputPath, outputPath) => {
+        // Fetched inside the job, so it waits for a slot and is skipped when
+        // lut3d is unavailable
+        const lutPath = await this.cachedLut(lutUrl);
+        heldLuts.push(lutPath);
+        return [
+          '-i', inputPath,
+          '-vf', `lut3d=file=${escapeFilterPath(lutPath)}:interp=tetrahedral`,
+          '-c:a', 'copy',
+          '-y',
+          outputPath
+        ];
+      });
+    } finally {
+      for (const lutPath of heldLuts) {
+        this.releaseLut(lutPath);
+      }
+    }
+  }
+
+  // Precise color correction from numeric settings (see ColorAdjustments for
+  // the ranges); out-of-range values are refused rather than clamped
+  async colorAdjust(videoUrl: string, adjustments: ColorAdjustments, options: EditOptions = {}): Promise<ProcessedVideo> {
+    for (const [name, value] of Object.entries(adjustments) as Array<[keyof ColorAdjustments, number | undefined]>) {
+      const [min, max] = COLOR_ADJUSTMENT_RANGES[name];
+      if (value !== undefined && (value < min || value > max)) {
+        throw new ProcessingError(`${name} must be between ${min} and ${max}, got ${value}`, 'ffmpeg', false);
+      }
+    }
+    const filter = colorAdjustFilter(adjustments);
+    if (!filter) {
+      throw new ProcessingError('No color adjustments given', 'ffmpeg', false);
+    }
     
-    const resultUrl = await this.uploadToVercelBlob(outputPath);
+    return this.processVideo('colorAdjust', videoUrl, options, (inputPath, outputPath) => [
+      '-i', inputPath,
+      '-vf', filter,
+      '-c:a', 'copy',
+      '-y',
+      outputPath
+    ]);
+  }
+
+  // Corrects a color cast. Auto mode assumes the scene averages out to grey
+  // over a few frames spread across the video and scales the red, green and
+  // blue channels to make it so; temperature and tint are applied on top.
+  async whiteBalance(videoUrl: string, balance: WhiteBalance, options: EditOptions = {}): Promise<ProcessedVideo> {
+    await this.ensureOperationSupported('whiteBalance', (['auto', 'temperature', 'tint'] as const).filter((mode) => balance[mode]));
+    return this.processVideo('whiteBalance', videoUrl, options, async (inputPath, outputPath) => {
+      const filters: string[] = [];
+      
+      if (balance.auto) {
+        const [red, green, blue] = await this.sampleAverageColor(inputPath);
+        const gray = (red + green + blue) / 3;
+        // Gains stay within an octave so a scene that really is mostly one
+        // color (a forest, a sunset) isn't wrecked
+        const gain = (channel: number) => Math.min(Math.max(gray / Math.max(channel, 1), 0.5), 2).toFixed(3);
+        filters.push(`colorchannelmixer=rr=${gain(red)}:gg=${gain(green)}:bb=${gain(blue)}`);
+      }
+      if (balance.temperature !== undefined && balance.temperature !== 6500) {
+        filters.push(`colortemperature=temperature=${balance.temperature}`);
+      }
+      if (balance.tint) {
+        // Magenta is less green; applied to the midtones where casts show most
+        filters.push(`colorbalance=gm=${(-0.3 * balance.tint).toFixed(3)}`);
+      }
+      if (filters.length === 0) {
+        throw new ProcessingError('No white balance change requested', 'ffmpeg', false);
+      }
+      
+      return [
+        '-i', inputPath,
+        '-vf', filters.join(','),
+        '-c:a', 'copy',
+        '-y',
+        outputPath
+      ];
+    });
+  }
+
+  // Mean RGB (0-255) of a few frames spread across the input, from
+  // signalstats' YUV averages
+  private async sampleAverageColor(inputPath: string): Promise<[number, number, number]> {
+    const { durationSeconds } = await this.probeMedia(inputPath);
+    const sampling = durationSeconds ? `fps=${WHITE_BALANCE_SAMPLES / durationSeconds},` : '';
+    const stderr = await this.runFFmpeg([
+      '-i', inputPath,
+      '-vf', `${sampling}scale=320:-2,signalstats,metadata=print`,
+      '-frames:v', String(WHITE_BALANCE_SAMPLES),
+      '-f', 'null',
+      '-'
+    ]);
     
-    fs.unlinkSync(inputPath);
-    fs.unlinkSync(outputPath);
+    const average = (key: string) => {
+      const values = [...stderr.matchAll(new RegExp(`lavfi\\.signalstats\\.${key}=([\\d.]+)`, 'g'))].map((match) => Number(match[1]));
+      if (values.length === 0) {
+        throw new ProcessingError('Could not sample frames to estimate white balance', 'ffmpeg', false);
+      }
+      return values.reduce((sum, value) => sum + value, 0) / values.length;
+    };
+    const y = average('YAVG');
+    const u = average('UAVG') - 128;
+    const v = average('VAVG') - 128;
     
-    return resultUrl;
+    // BT.601, which is close enough for an average
+    return [y + 1.402 * v, y - 0.344 * u - 0.714 * v, y + 1.772 * u];
   }
 }
 
-export const videoProcessor = new VideoProcessor(); 
\ No newline at end of file
+export const videoProcessor = new VideoProcessor();
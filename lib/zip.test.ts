@@ -0,0 +1,81 @@
+import assert from "node:assert/strict";
+import fs from "node:fs";
+import os from "node:os";
+import path from "node:path";
+import { test } from "node:test";
+
+import { writeZip } from "./zip";
+
+interface CentralEntry {
+  name: string;
+  crc: number;
+  size: number;
+  headerOffset: number;
+}
+
+// Reads the central directory back, as an unzip tool would
+function readCentralDirectory(zip: Buffer): CentralEntry[] {
+  const end = zip.length - 22;
+  assert.equal(zip.readUInt32LE(end), 0x06054b50);
+  const count = zip.readUInt16LE(end + 10);
+  let offset = zip.readUInt32LE(end + 16);
+  assert.equal(offset + zip.readUInt32LE(end + 12), end);
+
+  const entries: CentralEntry[] = [];
+  for (let index = 0; index < count; index++) {
+    assert.equal(zip.readUInt32LE(offset), 0x02014b50);
+    const nameLength = zip.readUInt16LE(offset + 28);
+    entries.push({
+      name: zip.subarray(offset + 46, offset + 46 + nameLength).toString("utf8"),
+      crc: zip.readUInt32LE(offset + 16),
+      size: zip.readUInt32LE(offset + 24),
+      headerOffset: zip.readUInt32LE(offset + 42),
+    });
+    offset += 46 + nameLength;
+  }
+  return entries;
+}
+
+test("stores each file under its name, byte for byte", async () => {
+  const dir = fs.mkdtempSync(path.join(os.tmpdir(), "zip-test-"));
+  try {
+    const first = path.join(dir, "a.bin");
+    const second = path.join(dir, "b.txt");
+    fs.writeFileSync(first, Buffer.from(Array.from({ length: 300000 }, (_, index) => index % 251)));
+    fs.writeFileSync(second, "hello");
+
+    const zipPath = path.join(dir, "out.zip");
+    await writeZip(zipPath, [
+      { name: "clip_part-1.mp4", filePath: first },
+      { name: "clip_part-2 é.txt", filePath: second },
+    ]);
+
+    const zip = fs.readFileSync(zipPath);
+    const entries = readCentralDirectory(zip);
+    assert.deepEqual(entries.map((entry) => entry.name), ["clip_part-1.mp4", "clip_part-2 é.txt"]);
+
+    for (const [entry, filePath] of [[entries[0], first], [entries[1], second]] as const) {
+      const original = fs.readFileSync(filePath);
+      assert.equal(zip.readUInt32LE(entry.headerOffset), 0x04034b50);
+      const dataStart = entry.headerOffset + 30 + zip.readUInt16LE(entry.headerOffset + 26);
+      assert.equal(entry.size, original.length);
+      assert.deepEqual(zip.subarray(dataStart, dataStart + entry.size), original);
+    }
+
+    // CRC-32 of "hello"
+    assert.equal(entries[1].crc, 0x3610a686);
+  } finally {
+    fs.rmSync(dir, { recursive: true, force: true });
+  }
+});
+
+test("writes a valid empty archive", async () => {
+  const dir = fs.mkdtempSync(path.join(os.tmpdir(), "zip-test-"));
+  try {
+    const zipPath = path.join(dir, "empty.zip");
+    await writeZip(zipPath, []);
+    assert.deepEqual(readCentralDirectory(fs.readFileSync(zipPath)), []);
+  } finally {
+    fs.rmSync(dir, { recursive: true, force: true });
+  }
+});
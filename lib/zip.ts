@@ -0,0 +1,123 @@
+// Minimal ZIP writer for bundling an edit's outputs into one download. Media
+// is already compressed, so entries are stored as-is; each file is streamed
+// through once, its CRC computed on the way, and the sizes written after the
+// data (a data descriptor), so nothing is held in memory or read twice.
+
+import fs from "fs";
+import { once } from "events";
+
+export interface ZipEntry {
+  // Name inside the archive
+  name: string;
+  filePath: string;
+}
+
+// Without ZIP64 extensions sizes and offsets must fit in 32 bits
+const MAX_ZIP_BYTES = 0xffffffff;
+
+const CRC_TABLE = new Uint32Array(256).map((_, index) => {
+  let crc = index;
+  for (let bit = 0; bit < 8; bit++) {
+    crc = crc & 1 ? 0xedb88320 ^ (crc >>> 1) : crc >>> 1;
+  }
+  return crc;
+});
+
+function updateCrc(crc: number, chunk: Buffer): number {
+  for (const byte of chunk) {
+    crc = CRC_TABLE[(crc ^ byte) & 0xff] ^ (crc >>> 8);
+  }
+  return crc;
+}
+
+// MS-DOS date and time fields, at two-second resolution
+function dosDateTime(date: Date): { time: number; date: number } {
+  return {
+    time: (date.getHours() << 11) | (date.getMinutes() << 5) | Math.floor(date.getSeconds() / 2),
+    date: ((date.getFullYear() - 1980) << 9) | ((date.getMonth() + 1) << 5) | date.getDate(),
+  };
+}
+
+export async function writeZip(outputPath: string, entries: ZipEntry[]): Promise<void> {
+  const totalBytes = entries.reduce((sum, entry) => sum + fs.statSync(entry.filePath).size, 0);
+  if (totalBytes > MAX_ZIP_BYTES) {
+    throw new Error("Outputs are too large to bundle into a zip (over 4GB)");
+  }
+
+  const output = fs.createWriteStream(outputPath);
+  let offset = 0;
+  const write = async (buffer: Buffer) => {
+    offset += buffer.length;
+    if (!output.write(buffer)) {
+      await once(output, "drain");
+    }
+  };
+
+  // Bit 3: sizes and CRC follow the data; bit 11: names are UTF-8
+  const flags = 0x0808;
+  const { time, date } = dosDateTime(new Date());
+  const central: Buffer[] = [];
+
+  try {
+    for (const entry of entries) {
+      const name = Buffer.from(entry.name, "utf8");
+      const headerOffset = offset;
+
+      const local = Buffer.alloc(30);
+      local.writeUInt32LE(0x04034b50, 0);
+      local.writeUInt16LE(20, 4);
+      local.writeUInt16LE(flags, 6);
+      local.writeUInt16LE(0, 8);
+      local.writeUInt16LE(time, 10);
+      local.writeUInt16LE(date, 12);
+      local.writeUInt16LE(name.length, 26);
+      await write(Buffer.concat([local, name]));
+
+      let crc = 0xffffffff;
+      let size = 0;
+      for await (const chunk of fs.createReadStream(entry.filePath)) {
+        crc = updateCrc(crc, chunk as Buffer);
+        size += (chunk as Buffer).length;
+        await write(chunk as Buffer);
+      }
+      crc = (crc ^ 0xffffffff) >>> 0;
+
+      const descriptor = Buffer.alloc(16);
+      descriptor.writeUInt32LE(0x08074b50, 0);
+      descriptor.writeUInt32LE(crc, 4);
+      descriptor.writeUInt32LE(size, 8);
+      descriptor.writeUInt32LE(size, 12);
+      await write(descriptor);
+
+      const record = Buffer.alloc(46);
+      record.writeUInt32LE(0x02014b50, 0);
+      record.writeUInt16LE(20, 4);
+      record.writeUInt16LE(20, 6);
+      record.writeUInt16LE(flags, 8);
+      record.writeUInt16LE(0, 10);
+      record.writeUInt16LE(time, 12);
+      record.writeUInt16LE(date, 14);
+      record.writeUInt32LE(crc, 16);
+      record.writeUInt32LE(size, 20);
+      record.writeUInt32LE(size, 24);
+      record.writeUInt16LE(name.length, 28);
+      record.writeUInt32LE(headerOffset, 42);
+      central.push(record, name);
+    }
+
+    const directory = Buffer.concat(central);
+    const directoryOffset = offset;
+    await write(directory);
+
+    const end = Buffer.alloc(22);
+    end.writeUInt32LE(0x06054b50, 0);
+    end.writeUInt16LE(entries.length, 8);
+    end.writeUInt16LE(entries.length, 10);
+    end.writeUInt32LE(directory.length, 12);
+    end.writeUInt32LE(directoryOffset, 16);
+    await write(end);
+  } finally {
+    output.end();
+    await once(output, "close");
+  }
+}